@@ -4,6 +4,14 @@ use std::collections::HashMap;
 #[derive(Debug, Clone)]
 pub struct ArgMap {
     pub values: HashMap<String, Option<ArgValue>>,
+    /// Name of the subcommand that was selected, if the reader has any
+    pub subcommand: Option<String>,
+    /// Positional arguments past the bound ones, see `ArgReader.collect_rest()`
+    pub rest: Vec<String>,
+    /// Arguments after `--`, kept as they were passed
+    pub trailing: Vec<String>,
+    /// Values of arguments as they were passed, before `ArgValue::parse`
+    pub raw: HashMap<String, String>,
 }
 
 impl ArgMap {
@@ -17,7 +25,7 @@ impl ArgMap {
                 eprintln!("Register '{}', via ArgReader.register({})", key, key);
                 panic!("Unregistered Argument being accessed: '{}'", key);
             }
-            Some(value) => return value.clone(),
+            Some(value) => value.clone(),
         }
     }
 
@@ -53,6 +61,26 @@ impl ArgMap {
         }
     }
 
+    /// The value of `key` as it was passed, for values that must not be read as numbers or bools
+    ///
+    /// # Example
+    /// ```
+    /// use arg_reader::ArgReader;
+    ///
+    /// let args = ArgReader::new()
+    ///     .register("output")
+    ///     .bind_with_required(vec!["o"])
+    ///     .read_args(vec!["-o".to_string(), "007".to_string()])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(args.get_as_string("output"), None);
+    /// assert_eq!(args.get_raw("output"), Some("007".to_string()));
+    /// ```
+    pub fn get_raw(&self, key: &str) -> Option<String> {
+        self.get(key)?;
+        self.raw.get(key).cloned()
+    }
+
     pub fn flag_is_set(&self, key: &str) -> bool {
         let value = self.get(key);
         match value {
@@ -1,6 +1,7 @@
 use crate::arg_map::ArgMap;
 use crate::arg_value::ArgValue;
 use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
 
 #[derive(Debug)]
 pub enum ReaderError {
@@ -8,6 +9,19 @@ pub enum ReaderError {
     UnknownArgument(String),
 }
 
+impl Display for ReaderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReaderError::MissingRequiredArgument(arg) => {
+                write!(f, "Missing value for '{}'", correct_binding(arg))
+            }
+            ReaderError::UnknownArgument(arg) => write!(f, "Unknown argument '{}'", arg),
+        }
+    }
+}
+
+impl std::error::Error for ReaderError {}
+
 pub fn correct_binding(binding: &str) -> String {
     if binding.len() == 1 {
         format!("-{}", binding)
@@ -22,9 +36,17 @@ pub struct ArgReader {
     bindings: HashMap<String, String>,
     bindings_with_required: HashMap<String, String>,
     positional_args: VecDeque<String>,
+    collect_rest: bool,
+    subcommands: HashMap<String, ArgReader>,
     current_arg: Option<String>,
 }
 
+impl Default for ArgReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ArgReader {
     pub fn new() -> Self {
         Self {
@@ -32,6 +54,8 @@ impl ArgReader {
             bindings: HashMap::new(),
             bindings_with_required: HashMap::new(),
             positional_args: VecDeque::new(),
+            collect_rest: false,
+            subcommands: HashMap::new(),
             current_arg: None,
         }
     }
@@ -52,10 +76,11 @@ impl ArgReader {
     /// let args = ArgReader::new()
     ///      .register("verbose")
     ///      .bind(vec!["v", "verbose"]) // will turn verbose true if -v or --verbose is passed
-    ///      .read_args(env_args);
+    ///      .read_args(env_args)
+    ///      .unwrap();
     ///
     ///
-    /// assert_eq!(args.get("verbose").unwrap().as_ref().unwrap().as_bool(), Some(true));
+    /// assert_eq!(args.get("verbose").unwrap().as_bool(), Some(true));
     /// ```
     pub fn register(mut self, arg: &str) -> Self {
         if self.current_arg.is_some() {
@@ -154,6 +179,77 @@ impl ArgReader {
         self
     }
 
+    /// Keep positional arguments past the bound ones in `ArgMap.rest` instead of rejecting them
+    pub fn collect_rest(mut self) -> Self {
+        self.collect_rest = true;
+        self
+    }
+
+    /// Add a subcommand with its own arguments
+    ///
+    /// The first positional argument naming a subcommand selects it, everything after it is read
+    /// by `reader`. Arguments registered on this reader are shared, they are accepted before and
+    /// after the subcommand name.
+    ///
+    /// # Example
+    /// ```
+    /// use arg_reader::ArgReader;
+    ///
+    /// let env_args = vec!["-v", "build", "main.bin", "-o", "main.blc"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    ///
+    /// let build = ArgReader::new()
+    ///     .register("file")
+    ///     .register("output")
+    ///     .bind_with_required(vec!["o", "output"])
+    ///     .bind_positional("file");
+    ///
+    /// let args = ArgReader::new()
+    ///     .register("verbose")
+    ///     .bind(vec!["v", "verbose"])
+    ///     .subcommand("build", build)
+    ///     .read_args(env_args)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(args.subcommand.as_deref(), Some("build"));
+    /// assert_eq!(args.get_as_string("output"), Some("main.blc".to_string()));
+    /// assert!(args.flag_is_set("verbose"));
+    /// ```
+    pub fn subcommand(mut self, name: &str, reader: ArgReader) -> Self {
+        self.subcommands.insert(name.to_string(), reader);
+        self
+    }
+
+    /// `reader` with the arguments of this reader added, its own bindings win
+    fn share_with(&self, mut reader: ArgReader) -> ArgReader {
+        if let Some(arg) = reader.current_arg.take() {
+            reader.args.insert(arg, None);
+        }
+
+        for (arg, value) in self.args.iter() {
+            reader.args.entry(arg.clone()).or_insert(None);
+            if value.is_some() {
+                reader.args.insert(arg.clone(), value.clone());
+            }
+        }
+        for (binding, arg) in self.bindings.iter() {
+            reader
+                .bindings
+                .entry(binding.clone())
+                .or_insert_with(|| arg.clone());
+        }
+        for (binding, arg) in self.bindings_with_required.iter() {
+            reader
+                .bindings_with_required
+                .entry(binding.clone())
+                .or_insert_with(|| arg.clone());
+        }
+
+        reader
+    }
+
     /// Read arguments from the command line and return a ArgMap
     ///
     /// # Panics
@@ -166,7 +262,28 @@ impl ArgReader {
     ///
     /// # Returns
     ///
-    /// A ArgMap, you can extract the values from the ArgMap
+    /// A ArgMap, you can extract the values from the ArgMap.
+    /// Everything after `--` is not parsed and ends up in `ArgMap.trailing`
+    /// Unknown subcommands are reported as `ReaderError::UnknownArgument`
+    ///
+    /// # Examples
+    /// ```
+    /// use arg_reader::ArgReader;
+    ///
+    /// let env_args = vec!["main.blc", "--", "--verbose", "1"]
+    ///     .into_iter()
+    ///     .map(String::from)
+    ///     .collect();
+    ///
+    /// let args = ArgReader::new()
+    ///      .register("file")
+    ///      .bind_positional("file")
+    ///      .read_args(env_args)
+    ///      .unwrap();
+    ///
+    /// assert_eq!(args.get_as_string("file"), Some("main.blc".to_string()));
+    /// assert_eq!(args.trailing, vec!["--verbose", "1"]);
+    /// ```
     pub fn read_args(mut self, args: Vec<String>) -> Result<ArgMap, ReaderError> {
         if self.current_arg.is_some() {
             self.args
@@ -174,15 +291,22 @@ impl ArgReader {
         }
 
         let mut args = args.iter();
+        let mut rest = vec![];
+        let mut trailing = vec![];
+        let mut raw = HashMap::new();
 
         while let Some(arg) = args.next() {
-            if let Some(arg) = self.bindings.get(&*arg) {
+            if arg == "--" {
+                trailing = args.cloned().collect();
+                break;
+            } else if let Some(arg) = self.bindings.get(arg) {
                 self.args
                     .insert(arg.to_string(), Some(ArgValue::from(true)));
-            } else if let Some(arg) = self.bindings_with_required.get(&*arg) {
+            } else if let Some(arg) = self.bindings_with_required.get(arg) {
                 if let Some(value) = args.next() {
                     self.args
                         .insert(arg.to_string(), Some(ArgValue::parse(value)));
+                    raw.insert(arg.to_string(), value.to_string());
                 } else {
                     return Err(ReaderError::MissingRequiredArgument(arg.to_string()));
                 }
@@ -191,24 +315,43 @@ impl ArgReader {
                 let arg_name = args.next().unwrap();
                 let value = args.next().unwrap();
 
-                if let Some(arg) = self.bindings_with_required.get(&*arg_name) {
+                if let Some(arg) = self.bindings_with_required.get(arg_name) {
                     self.args
                         .insert(arg.to_string(), Some(ArgValue::parse(value)));
+                    raw.insert(arg.to_string(), value.to_string());
                 } else {
                     return Err(ReaderError::UnknownArgument(arg.to_string()));
                 }
             } else {
                 if arg.starts_with("-") {
                     return Err(ReaderError::UnknownArgument(arg.to_string()));
+                } else if !self.subcommands.is_empty() {
+                    let reader = match self.subcommands.remove(arg) {
+                        Some(reader) => self.share_with(reader),
+                        None => return Err(ReaderError::UnknownArgument(arg.to_string())),
+                    };
+
+                    let mut map = reader.read_args(args.cloned().collect())?;
+                    map.subcommand = Some(arg.to_string());
+                    return Ok(map);
                 } else if let Some(arg_name) = self.positional_args.pop_front() {
                     self.args
-                        .insert(arg_name.to_string(), Some(ArgValue::parse(&arg)));
+                        .insert(arg_name.to_string(), Some(ArgValue::parse(arg)));
+                    raw.insert(arg_name.to_string(), arg.to_string());
+                } else if self.collect_rest {
+                    rest.push(arg.to_string());
                 } else {
                     return Err(ReaderError::UnknownArgument(arg.to_string()));
                 }
             }
         }
 
-        return Ok(ArgMap { values: self.args });
+        Ok(ArgMap {
+            values: self.args,
+            subcommand: None,
+            rest,
+            trailing,
+            raw,
+        })
     }
 }
@@ -0,0 +1,52 @@
+use arg_reader::ArgReader;
+use bincore::assembly::assembler::assemble;
+use bincore::data::container;
+use std::fs;
+
+fn main() {
+    let args = ArgReader::new()
+        .register("file")
+        .bind_with_required(vec!["i", "input"])
+        .register("output")
+        .bind_with_required(vec!["o", "output"])
+        .bind_positional("file")
+        .bind_positional("output")
+        .read_args(std::env::args().skip(1).collect())
+        .unwrap();
+
+    let input_file_name = args.get_as_string("file").unwrap_or_else(|| {
+        eprintln!("No file specified, use -i or --input");
+        std::process::exit(1);
+    });
+
+    let text = match fs::read_to_string(&input_file_name) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error reading file: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let program = match assemble(&text) {
+        Ok(program) => program,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("Error: {} {}", input_file_name, error);
+            }
+            std::process::exit(1);
+        }
+    };
+
+    let output = args.get_as_string("output").unwrap_or_else(|| {
+        let file_name = input_file_name.split('.').next().unwrap();
+        format!("{}.blc", file_name)
+    });
+
+    println!("Writing to file: {}", output);
+
+    let assembler_version = format!("binasm {}", env!("CARGO_PKG_VERSION"));
+    if let Err(e) = fs::write(&output, container::encode(&program, &assembler_version)) {
+        eprintln!("Error writing file: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,44 @@
+use std::path::{Path, PathBuf};
+
+/// Sources that decide what the compiler emits, bincore holds the instruction set, the container
+/// format and the extern manifest
+const SOURCES: &[&str] = &["src", "bin_grammar.pest", "../bincore/src"];
+
+/// Sets `BINCOMPILER_FINGERPRINT` to a hash of `SOURCES`, so a compiler built from changed
+/// sources never reuses programs cached by an older build with the same version
+fn main() {
+    let manifest_directory = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+
+    let mut files = vec![];
+    for source in SOURCES {
+        println!("cargo:rerun-if-changed={}", source);
+        collect(&manifest_directory.join(source), &mut files);
+    }
+    files.sort();
+
+    // FNV-1a over every file name and its contents
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for file in files {
+        let name = file.strip_prefix(&manifest_directory).unwrap_or(&file);
+        let contents = std::fs::read(&file).unwrap_or_default();
+        for byte in name.to_string_lossy().bytes().chain(contents) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    println!("cargo:rustc-env=BINCOMPILER_FINGERPRINT={:016x}", hash);
+}
+
+/// Every file under `path`, a missing path adds nothing
+fn collect(path: &Path, files: &mut Vec<PathBuf>) {
+    match std::fs::read_dir(path) {
+        Ok(entries) => {
+            for entry in entries.flatten() {
+                collect(&entry.path(), files);
+            }
+        }
+        Err(_) if path.is_file() => files.push(path.to_path_buf()),
+        Err(_) => {}
+    }
+}
@@ -1,3 +1,4 @@
+use bincore::data::debug_info::Position;
 use std::fmt::Display;
 
 #[derive(Debug, Clone)]
@@ -16,63 +17,79 @@ impl Display for Identifier {
     }
 }
 
+/// Function calls keep where their name starts and operators where the operator is, so errors in
+/// nested expressions point at them
 #[derive(Debug, Clone)]
 pub enum Expression {
     Int(i64),
     Float(f64),
     String(String),
     Bool(bool),
+    Null,
     Variable(Identifier),
     FunctionCall {
         func_name: Identifier,
         args: Vec<Expression>,
+        position: Position,
     },
     Addition {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     Subtraction {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     Multiplication {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     Division {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     Modulus {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     Equal {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     NotEqual {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     GreaterThan {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     LessThan {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     GreaterThanOrEqual {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
     LessThanOrEqual {
         left: Box<Expression>,
         right: Box<Expression>,
+        position: Position,
     },
 }
 
+#[allow(dead_code, clippy::upper_case_acronyms)]
 #[derive(Debug, Clone)]
 pub enum Statement {
     Assignment {
@@ -90,15 +107,15 @@ pub enum Statement {
     FunctionDeclaration {
         func_name: Identifier,
         args: Vec<Identifier>,
-        body: Vec<Statement>,
+        body: Block,
     },
     Conditional {
-        body: Vec<(Expression, Vec<Statement>)>,
-        else_body: Option<Vec<Statement>>,
+        body: Vec<(Expression, Block)>,
+        else_body: Option<Block>,
     },
     WhileLoop {
         condition: Expression,
-        body: Vec<Statement>,
+        body: Block,
     },
     Break,
     Continue,
@@ -106,3 +123,23 @@ pub enum Statement {
     Return(Expression),
     Comment(String),
 }
+
+/// Where a statement starts and the line it ends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+}
+
+impl Span {
+    pub fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+/// Statements in source order, each with where it is in the source
+pub type Block = Vec<(Statement, Span)>;
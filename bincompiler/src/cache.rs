@@ -0,0 +1,139 @@
+use crate::{compile, CompileError, COMPILER_BUILD};
+use bincore::data::container;
+use bincore::data::program_file::Program;
+use std::path::PathBuf;
+
+/// Compiled programs stored by a hash of their source, so running an unchanged file skips the compiler
+pub struct Cache {
+    pub directory: PathBuf,
+}
+
+impl Cache {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Cache {
+        Cache {
+            directory: directory.into(),
+        }
+    }
+
+    /// `$BINISAYA_CACHE`, otherwise `binisaya` in `$XDG_CACHE_HOME` or `$HOME/.cache`
+    pub fn default_directory() -> Option<PathBuf> {
+        if let Some(directory) = std::env::var_os("BINISAYA_CACHE") {
+            return Some(PathBuf::from(directory));
+        }
+
+        let cache_home = match std::env::var_os("XDG_CACHE_HOME") {
+            Some(directory) => PathBuf::from(directory),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+        };
+        Some(cache_home.join("binisaya"))
+    }
+
+    /// FNV-1a of everything the compiled program depends on, as 32 hex digits
+    ///
+    /// The source file name is part of the key because it ends up in the debug info, the compiler
+    /// build so that rebuilding the compiler from changed sources starts from an empty cache.
+    pub fn key(source: &str, source_file: &str) -> String {
+        let mut hash = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58du128;
+        for part in [COMPILER_BUILD, source_file, source] {
+            for byte in part.bytes().chain(std::iter::once(0)) {
+                hash ^= byte as u128;
+                hash = hash.wrapping_mul(0x0000_0000_0100_0000_0000_0000_0000_013b);
+            }
+        }
+
+        format!("{:032x}", hash)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.blc", key))
+    }
+
+    /// The cached program, unreadable entries and ones written by another compiler build count as
+    /// missing
+    pub fn get(&self, key: &str) -> Option<Program> {
+        let bytes = std::fs::read(self.path(key)).ok()?;
+        match container::decode(&bytes) {
+            Ok((header, program)) if header.compiler_version == COMPILER_BUILD => Some(program),
+            _ => None,
+        }
+    }
+
+    pub fn put(&self, key: &str, program: &Program) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        // renaming keeps a concurrent run from reading a half written entry
+        let path = self.path(key);
+        let partial = path.with_extension(format!("{}.partial", std::process::id()));
+        std::fs::write(&partial, container::encode(program, COMPILER_BUILD))?;
+        std::fs::rename(&partial, &path)
+    }
+
+    /// Compiles `source` unless it is cached, failing to store the result is not an error
+    pub fn compile(&self, source: &str, source_file: &str) -> Result<Program, CompileError> {
+        let key = Cache::key(source, source_file);
+        if let Some(program) = self.get(&key) {
+            return Ok(program);
+        }
+
+        let program = compile(source, source_file)?;
+        let _ = self.put(&key, &program);
+
+        Ok(program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(test: &str) -> Cache {
+        let directory =
+            std::env::temp_dir().join(format!("bincompiler-cache-{}-{}", std::process::id(), test));
+        let _ = std::fs::remove_dir_all(&directory);
+        Cache::new(directory)
+    }
+
+    #[test]
+    fn keys_depend_on_the_file_name_and_the_source() {
+        let key = Cache::key("ipakita(1);", "main.bin");
+
+        assert_eq!(key.len(), 32);
+        assert_eq!(key, Cache::key("ipakita(1);", "main.bin"));
+        assert_ne!(key, Cache::key("ipakita(2);", "main.bin"));
+        assert_ne!(key, Cache::key("ipakita(1);", "other.bin"));
+        // parts are separated, so moving text between them changes the key
+        assert_ne!(Cache::key("a", "main.bin"), Cache::key("", "main.bina"));
+    }
+
+    #[test]
+    fn compiles_once_and_reuses_the_entry() {
+        let cache = cache("reuse");
+        let key = Cache::key("deklara x = 1;", "main.bin");
+
+        assert!(cache.get(&key).is_none());
+        let program = cache.compile("deklara x = 1;", "main.bin").unwrap();
+        assert_eq!(cache.get(&key), Some(program));
+
+        std::fs::remove_dir_all(&cache.directory).unwrap();
+    }
+
+    #[test]
+    fn entries_of_other_compiler_builds_are_missing() {
+        let cache = cache("other");
+        let key = Cache::key("deklara x = 1;", "main.bin");
+        let program = compile("deklara x = 1;", "main.bin").unwrap();
+
+        std::fs::create_dir_all(&cache.directory).unwrap();
+        std::fs::write(
+            cache.path(&key),
+            container::encode(&program, "bincompiler 0.0.0 0000000000000000"),
+        )
+        .unwrap();
+        assert!(cache.get(&key).is_none());
+
+        std::fs::write(cache.path(&key), b"not a program").unwrap();
+        assert!(cache.get(&key).is_none());
+
+        std::fs::remove_dir_all(&cache.directory).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use crate::ast::{Expression, Span, Statement};
+use crate::parser::BinLangParse;
+use crate::CompileError;
+
+const INDENT: &str = "    ";
+
+/// Formats a source file: one statement per line ending in `;`, four space indentation, spaces
+/// around operators and only the parentheses precedence needs
+///
+/// Comments are kept, a comment on the same line as the statement before it stays there, and
+/// runs of blank lines between statements become a single one.
+///
+/// # Example
+/// ```
+/// use bincompiler::format::format_source;
+///
+/// let source = "deklara x=(1+2)*3\nkung x>5{ipakita( x );}";
+///
+/// assert_eq!(
+///     format_source(source).unwrap(),
+///     "deklara x = (1 + 2) * 3;\nkung x > 5 {\n    ipakita(x);\n}\n"
+/// );
+/// ```
+pub fn format_source(source: &str) -> Result<String, CompileError> {
+    let statements =
+        BinLangParse::statements(source).map_err(|error| CompileError::Parse(error.to_string()))?;
+
+    let mut text = String::new();
+    block(&mut text, &statements, 0);
+
+    Ok(text)
+}
+
+fn block(text: &mut String, statements: &[(Statement, Span)], depth: usize) {
+    let indent = INDENT.repeat(depth);
+
+    // line the statement before ended on
+    let mut previous_end = None;
+
+    for (statement, span) in statements {
+        if let Statement::EOI = statement {
+            continue;
+        }
+
+        match previous_end {
+            Some(previous_end)
+                if matches!(statement, Statement::Comment(_)) && span.line == previous_end =>
+            {
+                // trailing comment, goes back onto the line of the statement before it
+                text.pop();
+                text.push(' ');
+            }
+            Some(previous_end) if span.line > previous_end + 1 => {
+                text.push('\n');
+                text.push_str(&indent);
+            }
+            _ => text.push_str(&indent),
+        }
+        previous_end = Some(span.end_line);
+
+        self::statement(text, statement, depth);
+        text.push('\n');
+    }
+}
+
+fn body(text: &mut String, statements: &[(Statement, Span)], depth: usize) {
+    text.push_str("{\n");
+    block(text, statements, depth + 1);
+    text.push_str(&INDENT.repeat(depth));
+    text.push('}');
+}
+
+fn statement(text: &mut String, statement: &Statement, depth: usize) {
+    match statement {
+        Statement::Assignment {
+            identifier,
+            expression,
+        } => text.push_str(&format!(
+            "deklara {} = {};",
+            identifier,
+            self::expression(expression)
+        )),
+        Statement::Reassignment {
+            identifier,
+            expression,
+        } => text.push_str(&format!(
+            "{} = {};",
+            identifier,
+            self::expression(expression)
+        )),
+        Statement::FunctionCall { func_name, args } => {
+            text.push_str(&format!("{};", call(&func_name.to_string(), args)))
+        }
+        Statement::FunctionDeclaration {
+            func_name,
+            args,
+            body,
+        } => {
+            let args = args
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<String>>();
+            text.push_str(&format!("proseso {}({}) ", func_name, args.join(", ")));
+            self::body(text, body, depth);
+        }
+        Statement::Conditional { body, else_body } => {
+            for (index, (condition, statements)) in body.iter().enumerate() {
+                if index > 0 {
+                    text.push_str(" kung lahi ");
+                } else {
+                    text.push_str("kung ");
+                }
+                text.push_str(&expression(condition));
+                text.push(' ');
+                self::body(text, statements, depth);
+            }
+            if let Some(statements) = else_body {
+                text.push_str(" kana ");
+                self::body(text, statements, depth);
+            }
+        }
+        Statement::WhileLoop { condition, body } => {
+            text.push_str(&format!("samtang {} ", expression(condition)));
+            self::body(text, body, depth);
+        }
+        Statement::Break => text.push_str("human;"),
+        Statement::Continue => text.push_str("tiwas;"),
+        Statement::Return(value) => text.push_str(&format!("ibalik {};", expression(value))),
+        Statement::Comment(comment) => text.push_str(comment.trim_end()),
+        Statement::EOI => {}
+    }
+}
+
+fn call(func_name: &str, args: &[Expression]) -> String {
+    let args = args.iter().map(expression).collect::<Vec<String>>();
+    format!("{}({})", func_name, args.join(", "))
+}
+
+/// Binding strength of the grammar rule an expression comes from, `expr`, `sum`, `product`, `term`
+fn precedence(expression: &Expression) -> usize {
+    match expression {
+        Expression::Equal { .. }
+        | Expression::NotEqual { .. }
+        | Expression::GreaterThan { .. }
+        | Expression::LessThan { .. }
+        | Expression::GreaterThanOrEqual { .. }
+        | Expression::LessThanOrEqual { .. } => 0,
+        Expression::Addition { .. } | Expression::Subtraction { .. } => 1,
+        Expression::Multiplication { .. }
+        | Expression::Division { .. }
+        | Expression::Modulus { .. } => 2,
+        _ => 3,
+    }
+}
+
+fn expression(expression: &Expression) -> String {
+    operand(expression, 0)
+}
+
+/// `expression` in a position that needs at least `precedence`, in parentheses if it binds looser
+fn operand(expression: &Expression, precedence: usize) -> String {
+    let (left, operator, right) = match expression {
+        Expression::Int(value) => return value.to_string(),
+        Expression::Float(value) => return format!("{:?}", value),
+        Expression::String(value) => return format!("\"{}\"", value),
+        Expression::Bool(value) => return value.to_string(),
+        Expression::Null => return "wala".to_string(),
+        Expression::Variable(identifier) => return identifier.to_string(),
+        Expression::FunctionCall {
+            func_name, args, ..
+        } => return call(&func_name.to_string(), args),
+        Expression::Addition { left, right, .. } => (left, "+", right),
+        Expression::Subtraction { left, right, .. } => (left, "-", right),
+        Expression::Multiplication { left, right, .. } => (left, "*", right),
+        Expression::Division { left, right, .. } => (left, "/", right),
+        Expression::Modulus { left, right, .. } => (left, "%", right),
+        Expression::Equal { left, right, .. } => (left, "==", right),
+        Expression::NotEqual { left, right, .. } => (left, "!=", right),
+        Expression::GreaterThan { left, right, .. } => (left, ">", right),
+        Expression::LessThan { left, right, .. } => (left, "<", right),
+        Expression::GreaterThanOrEqual { left, right, .. } => (left, ">=", right),
+        Expression::LessThanOrEqual { left, right, .. } => (left, "<=", right),
+    };
+
+    // operators group to the left, so a right operand of the same strength needs parentheses
+    let own = self::precedence(expression);
+    let text = format!(
+        "{} {} {}",
+        operand(left, own),
+        operator,
+        operand(right, own + 1)
+    );
+
+    if own < precedence {
+        format!("({})", text)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,97 @@
+extern crate pest;
+#[macro_use]
+extern crate pest_derive;
+
+use crate::parser::BinLangParse;
+use crate::translation::{BinLangTranslationUnit, TranslationError};
+use bincore::data::container;
+use bincore::data::program_file::Program;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+pub mod ast;
+pub mod cache;
+pub mod format;
+pub mod parser;
+pub mod translation;
+
+/// Recorded in every program this crate writes
+pub const COMPILER_VERSION: &str = concat!("bincompiler ", env!("CARGO_PKG_VERSION"));
+
+/// `COMPILER_VERSION` with a hash of the sources the compiler was built from, see `build.rs`
+pub const COMPILER_BUILD: &str = concat!(
+    "bincompiler ",
+    env!("CARGO_PKG_VERSION"),
+    " ",
+    env!("BINCOMPILER_FINGERPRINT")
+);
+
+#[derive(Debug)]
+pub enum CompileError {
+    Parse(String),
+    Translation(Vec<TranslationError>),
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileError::Parse(message) => write!(f, "Syntax error\n{}", message),
+            CompileError::Translation(errors) => {
+                let errors = errors
+                    .iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<String>>();
+                write!(f, "{}", errors.join("\n"))
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// Parses and translates `source`, `source_file` is only recorded in the debug info
+pub fn compile(source: &str, source_file: &str) -> Result<Program, CompileError> {
+    let (statements, functions) = BinLangParse::data(source)
+        .map_err(|error| CompileError::Parse((*error).with_path(source_file).to_string()))?;
+
+    BinLangTranslationUnit::translate(source_file, statements, functions)
+        .map_err(CompileError::Translation)
+}
+
+/// Writes `program` in every format of `formats`, `blc` or `json`, returning the paths written
+///
+/// With more than one format each file is named after `output` with the format as extension.
+pub fn write_program(
+    program: &Program,
+    output: &str,
+    formats: &[String],
+) -> Result<Vec<String>, String> {
+    let mut paths = vec![];
+
+    for format in formats.iter() {
+        let contents = match format.as_str() {
+            "blc" => container::encode(program, COMPILER_VERSION),
+            "json" => container::encode_json(program, COMPILER_VERSION).into_bytes(),
+            _ => {
+                return Err(format!(
+                    "Unknown output format '{}', expected blc or json",
+                    format
+                ))
+            }
+        };
+
+        let path = if formats.len() > 1 {
+            Path::new(output)
+                .with_extension(format)
+                .to_string_lossy()
+                .to_string()
+        } else {
+            output.to_string()
+        };
+
+        std::fs::write(&path, contents).map_err(|e| format!("Error writing file: {}", e))?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
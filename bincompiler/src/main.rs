@@ -1,21 +1,17 @@
-extern crate pest;
-#[macro_use]
-extern crate pest_derive;
-
-use crate::parser::BinLangParse;
 use arg_reader::{ArgMap, ArgReader};
+use bincompiler::CompileError;
 use std::fs;
 
-mod ast;
-mod parser;
-mod translation;
-
 fn main() {
     let args: ArgMap = ArgReader::new()
         .register("file")
         .bind_with_required(vec!["i", "input"])
         .register("debug")
         .bind(vec!["d", "debug"])
+        .register("strip")
+        .bind(vec!["s", "strip"])
+        .register("emit")
+        .bind_with_required(vec!["e", "emit"])
         .register("help")
         .bind(vec!["h", "help"])
         .register("output")
@@ -38,19 +34,48 @@ fn main() {
         }
     };
 
-    let (data, funcs) = BinLangParse::data(&*file_data);
+    let mut program = match bincompiler::compile(&file_data, &input_file_name) {
+        Ok(program) => program,
+        Err(CompileError::Translation(errors)) => {
+            for error in errors {
+                eprintln!("Error: {}", error);
+            }
+            std::process::exit(1);
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            std::process::exit(1);
+        }
+    };
+
+    // debug info only holds names and positions, the program runs the same without it
+    if args.flag_is_set("strip") {
+        program.debug = None;
+    }
 
-    let program = translation::BinLangTranslationUnit::translate(data, funcs);
+    let formats = args
+        .get_as_string("emit")
+        .unwrap_or_else(|| "blc".to_string())
+        .split(',')
+        .map(|format| format.trim().to_string())
+        .collect::<Vec<String>>();
 
     let output = args.get_as_string("output").unwrap_or_else(|| {
         let file_name = input_file_name.split('.').next().unwrap();
-        format!("{}.blc", file_name)
+        format!("{}.{}", file_name, formats[0])
     });
 
-    println!("Writing to file: {}", output);
-
-    let mut file = fs::File::create(output).unwrap();
-    bincode::serialize_into(&mut file, &program).unwrap();
+    match bincompiler::write_program(&program, &output, &formats) {
+        Ok(paths) => {
+            for path in paths {
+                println!("Writing to file: {}", path);
+            }
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
 
     if args.flag_is_set("debug") {
         for (line, instruction) in program.instructions.iter().enumerate() {
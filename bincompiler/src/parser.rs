@@ -1,16 +1,22 @@
-use crate::ast::{Expression, Identifier, Statement};
+use crate::ast::{Block, Expression, Identifier, Span, Statement};
+use bincore::data::debug_info::Position;
+use pest::error::Error;
 use pest::iterators::Pair;
 use pest::Parser;
 
 macro_rules! binary {
-    ($left:expr, $right:expr, $op:ident) => {
+    ($left:expr, $right:expr, $operator:expr, $op:ident) => {
         Expression::$op {
             left: Box::new($left),
             right: Box::new($right),
+            position: Self::position(&$operator),
         }
     };
 }
 
+/// Top level statements and function declarations, or where parsing stopped
+pub type ParseResult = Result<(Block, Block), Box<Error<Rule>>>;
+
 #[derive(Parser)]
 #[grammar = "bin_grammar.pest"]
 struct BinLangParser;
@@ -18,9 +24,9 @@ pub struct BinLangParse;
 
 impl BinLangParse {
     pub fn identifier_with_dots(pair: Pair<Rule>) -> Identifier {
-        let mut pairs = pair.into_inner();
+        let pairs = pair.into_inner();
         let mut identifiers = vec![];
-        while let Some(pair) = pairs.next() {
+        for pair in pairs {
             identifiers.push(pair.as_str().to_string());
         }
 
@@ -44,15 +50,20 @@ impl BinLangParse {
         }
     }
     pub fn function_call_expr(pair: Pair<Rule>) -> Expression {
+        let position = Self::position(&pair);
         let mut pairs = pair.into_inner();
-        let func_name = Self::identifier(pairs.next().unwrap());
+        let func_name = Self::usable_identifier(pairs.next().unwrap());
         let mut args = vec![];
 
-        while let Some(pair) = pairs.next() {
+        for pair in pairs {
             args.push(Self::expr(pair));
         }
 
-        Expression::FunctionCall { func_name, args }
+        Expression::FunctionCall {
+            func_name,
+            args,
+            position,
+        }
     }
     pub fn term(pair: Pair<Rule>) -> Expression {
         let mut pairs = pair.into_inner();
@@ -67,6 +78,7 @@ impl BinLangParse {
             Rule::float => Expression::Float(current.as_str().parse().unwrap()),
             Rule::integer => Expression::Int(current.as_str().replace("_", "").parse().unwrap()),
             Rule::bool => Expression::Bool(current.as_str().parse().unwrap()),
+            Rule::null => Expression::Null,
             Rule::usable_identifier => Expression::Variable(Self::usable_identifier(current)),
             Rule::function_call => Self::function_call_expr(current),
             Rule::expr => Self::expr(current),
@@ -90,13 +102,18 @@ impl BinLangParse {
         while let Some(pair) = pairs.next() {
             match pair.as_str() {
                 "*" => {
-                    left = binary!(left, Self::term(pairs.next().unwrap()), Multiplication);
+                    left = binary!(
+                        left,
+                        Self::term(pairs.next().unwrap()),
+                        pair,
+                        Multiplication
+                    );
                 }
                 "/" => {
-                    left = binary!(left, Self::term(pairs.next().unwrap()), Division);
+                    left = binary!(left, Self::term(pairs.next().unwrap()), pair, Division);
                 }
                 "%" => {
-                    left = binary!(left, Self::term(pairs.next().unwrap()), Modulus);
+                    left = binary!(left, Self::term(pairs.next().unwrap()), pair, Modulus);
                 }
                 _ => unimplemented!(),
             }
@@ -114,10 +131,15 @@ impl BinLangParse {
         while let Some(pair) = pairs.next() {
             match pair.as_str() {
                 "+" => {
-                    left = binary!(left, Self::product(pairs.next().unwrap()), Addition);
+                    left = binary!(left, Self::product(pairs.next().unwrap()), pair, Addition);
                 }
                 "-" => {
-                    left = binary!(left, Self::product(pairs.next().unwrap()), Subtraction);
+                    left = binary!(
+                        left,
+                        Self::product(pairs.next().unwrap()),
+                        pair,
+                        Subtraction
+                    );
                 }
                 _ => unimplemented!(),
             }
@@ -136,22 +158,32 @@ impl BinLangParse {
         while let Some(pair) = pairs.next() {
             match pair.as_str() {
                 "==" => {
-                    left = binary!(left, Self::sum(pairs.next().unwrap()), Equal);
+                    left = binary!(left, Self::sum(pairs.next().unwrap()), pair, Equal);
                 }
                 "!=" => {
-                    left = binary!(left, Self::sum(pairs.next().unwrap()), NotEqual);
+                    left = binary!(left, Self::sum(pairs.next().unwrap()), pair, NotEqual);
                 }
                 "<" => {
-                    left = binary!(left, Self::sum(pairs.next().unwrap()), LessThan);
+                    left = binary!(left, Self::sum(pairs.next().unwrap()), pair, LessThan);
                 }
                 ">" => {
-                    left = binary!(left, Self::sum(pairs.next().unwrap()), GreaterThan);
+                    left = binary!(left, Self::sum(pairs.next().unwrap()), pair, GreaterThan);
                 }
                 "<=" => {
-                    left = binary!(left, Self::sum(pairs.next().unwrap()), LessThanOrEqual);
+                    left = binary!(
+                        left,
+                        Self::sum(pairs.next().unwrap()),
+                        pair,
+                        LessThanOrEqual
+                    );
                 }
                 ">=" => {
-                    left = binary!(left, Self::sum(pairs.next().unwrap()), GreaterThanOrEqual);
+                    left = binary!(
+                        left,
+                        Self::sum(pairs.next().unwrap()),
+                        pair,
+                        GreaterThanOrEqual
+                    );
                 }
                 _ => unimplemented!(),
             }
@@ -183,10 +215,10 @@ impl BinLangParse {
 
     pub fn function_call(pair: Pair<Rule>) -> Statement {
         let mut pairs = pair.into_inner();
-        let func_name = Self::identifier(pairs.next().unwrap());
+        let func_name = Self::usable_identifier(pairs.next().unwrap());
         let mut args = vec![];
 
-        while let Some(pair) = pairs.next() {
+        for pair in pairs {
             args.push(Self::expr(pair));
         }
 
@@ -265,17 +297,38 @@ impl BinLangParse {
         }
     }
 
-    pub fn block(pair: Pair<Rule>, in_a_loop: bool, in_a_function: bool) -> Vec<Statement> {
-        let mut pairs = pair.into_inner();
+    pub fn block(pair: Pair<Rule>, in_a_loop: bool, in_a_function: bool) -> Block {
+        let pairs = pair.into_inner();
         let mut statements = vec![];
 
-        while let Some(pair) = pairs.next() {
-            statements.push(Self::statement(pair, in_a_loop, in_a_function).expect("Parse Error"));
+        for pair in pairs {
+            let span = Self::span(&pair);
+            let statement = Self::statement(pair, in_a_loop, in_a_function).expect("Parse Error");
+            statements.push((statement, span));
         }
 
         statements
     }
 
+    /// Where `pair` starts
+    pub fn position(pair: &Pair<Rule>) -> Position {
+        let (line, column) = pair.as_span().start_pos().line_col();
+        Position { line, column }
+    }
+
+    /// Where the statement in `pair` starts and the line it ends on
+    pub fn span(pair: &Pair<Rule>) -> Span {
+        let (line, column) = pair.as_span().start_pos().line_col();
+        // some rules swallow the newlines after them
+        let end_line = line + pair.as_str().trim_end().matches('\n').count();
+
+        Span {
+            line,
+            column,
+            end_line,
+        }
+    }
+
     pub fn statement(pair: Pair<Rule>, in_a_loop: bool, in_a_function: bool) -> Option<Statement> {
         let data = match pair.as_rule() {
             Rule::variable_assignment => Self::variable_assignment(pair),
@@ -321,39 +374,44 @@ impl BinLangParse {
         Some(data)
     }
 
-    pub fn data(data: &str) -> (Vec<Statement>, Vec<Statement>) {
-        let pairs = match BinLangParser::parse(Rule::program, data) {
-            Ok(pairs) => pairs,
-            Err(e) => panic!("Error: {}", e),
-        };
+    /// Every top level statement in source order
+    pub fn statements(data: &str) -> Result<Block, Box<Error<Rule>>> {
+        let pairs = BinLangParser::parse(Rule::program, data)?;
 
         // for pair in pairs.clone() {
         //     println!("{:#?}", pair);
         // }
 
+        let mut statements = vec![];
+
+        for pair in pairs {
+            let span = Self::span(&pair);
+            if let Some(statement) = Self::statement(pair, false, false) {
+                statements.push((statement, span));
+            }
+        }
+
+        Ok(statements)
+    }
+
+    /// `data` as a single expression, optionally followed by a `;`
+    pub fn expression(data: &str) -> Result<Expression, Box<Error<Rule>>> {
+        let mut pairs = BinLangParser::parse(Rule::single_expression, data)?;
+
+        Ok(Self::expr(pairs.next().unwrap()))
+    }
+
+    pub fn data(data: &str) -> ParseResult {
         let mut statements = vec![];
         let mut functions = vec![];
 
-        for pair in pairs.clone() {
-            match Self::statement(pair, false, false) {
-                Some(Statement::FunctionDeclaration {
-                    func_name,
-                    args,
-                    body,
-                }) => {
-                    functions.push(Statement::FunctionDeclaration {
-                        func_name,
-                        args,
-                        body,
-                    });
-                }
-                Some(statement) => {
-                    statements.push(statement);
-                }
-                None => {}
+        for (statement, span) in Self::statements(data)? {
+            match statement {
+                Statement::FunctionDeclaration { .. } => functions.push((statement, span)),
+                statement => statements.push((statement, span)),
             }
         }
 
-        (statements, functions)
+        Ok((statements, functions))
     }
 }
@@ -1,20 +1,73 @@
-use crate::ast::{Expression, Statement};
+use crate::ast::{Block, Expression, Span, Statement};
+use bincore::data::debug_info::{DebugInfo, FunctionRange, Position};
+use bincore::data::function::FunctionExport;
 use bincore::data::program_file::Program;
 use bincore::data::value::Value;
 use bincore::executable::runnable::Instruction;
+use bincore::stdlib::NativeRegistry;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone)]
+pub enum TranslationError {
+    UnknownFunction(String),
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+        variadic: bool,
+    },
+    /// a function declared again by `append` takes another number of arguments than before
+    SignatureChanged {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+}
 
-const VARIADIC_FUNCTIONS: [&str; 1] = ["ipakita"];
-
-const EXTERNAL_FUNCTIONS: [&str; 1] = ["ipakita"];
+impl Display for TranslationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranslationError::UnknownFunction(name) => {
+                write!(f, "Unknown function '{}'", name)
+            }
+            TranslationError::ArgumentCount {
+                function,
+                expected,
+                found,
+                variadic,
+            } => write!(
+                f,
+                "Function '{}' expects {}{} argument(s), got {}",
+                function,
+                if *variadic { "at least " } else { "" },
+                expected,
+                found
+            ),
+            TranslationError::SignatureChanged {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Function '{}' is declared again with {} argument(s), its callers pass {}",
+                function, found, expected
+            ),
+        }
+    }
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum IntermediateCode {
     Label(String),
     Call(String),
     Inst(Instruction),
     Jump(String),
     JumpIfFalse(String),
+    /// the code up to the matching `EndLocation` comes from `Position`, unless it is located
+    /// again inside
+    Location(Position),
+    EndLocation,
 }
 
 pub struct IC;
@@ -35,12 +88,22 @@ impl IC {
     pub fn jump_if_false(name: &str) -> IntermediateCode {
         IntermediateCode::JumpIfFalse(name.to_string())
     }
+    /// `code` located at `position`
+    pub fn located(position: Position, code: Vec<IntermediateCode>) -> Vec<IntermediateCode> {
+        let mut located = vec![IntermediateCode::Location(position)];
+        located.extend(code);
+        located.push(IntermediateCode::EndLocation);
+        located
+    }
 }
 
+/// Where code that is not part of any statement, like the entry of a program, is located
+const START: Position = Position { line: 1, column: 1 };
+
+#[derive(Clone)]
 pub struct BinLangTranslationUnit {
-    pub statements: Vec<Statement>,
-    pub functions: Vec<Statement>,
-    pub code: Vec<IntermediateCode>,
+    pub statements: Block,
+    pub functions: Block,
 
     pub string_refs: HashMap<String, usize>,
     pub string_ref_by_index: HashMap<usize, String>,
@@ -48,6 +111,17 @@ pub struct BinLangTranslationUnit {
     pub variable_ref_by_index: HashMap<usize, String>,
 
     pub func_args: HashMap<String, Vec<String>>,
+    pub labels: HashMap<String, usize>,
+    /// source position of every emitted instruction, filled in by `run`
+    pub positions: Vec<Option<Position>>,
+
+    pub registry: NativeRegistry,
+    pub errors: Vec<TranslationError>,
+
+    /// address of every function body emitted, older versions of redeclared functions included
+    function_starts: Vec<(String, usize)>,
+    /// address the top level code of `run` and of every `append` starts at
+    entries: Vec<usize>,
 
     conditional_label_count: usize,
     while_label_count: usize,
@@ -83,10 +157,11 @@ impl BinLangTranslationUnit {
         let mut code = vec![];
 
         macro_rules! operation {
-            ($left:expr, $right:expr, $op:ident) => {
-                code.append(&mut self.expression($left));
-                code.append(&mut self.expression($right));
-                code.push(IC::instruction(Instruction::$op));
+            ($left:expr, $right:expr, $position:expr, $op:ident) => {
+                let mut operation = self.expression($left);
+                operation.append(&mut self.expression($right));
+                operation.push(IC::instruction(Instruction::$op));
+                code.extend(IC::located(*$position, operation));
             };
         }
 
@@ -103,7 +178,7 @@ impl BinLangTranslationUnit {
             }
             Expression::String(str) => {
                 code.push(IC::instruction(Instruction::Push {
-                    value: Value::StrRef(self.reference_string(&*str.to_string())),
+                    value: Value::StrRef(self.reference_string(&str.to_string())),
                 }));
             }
             Expression::Bool(value) => {
@@ -111,46 +186,98 @@ impl BinLangTranslationUnit {
                     value: Value::Bool(*value),
                 }));
             }
+            Expression::Null => {
+                code.push(IC::instruction(Instruction::Push { value: Value::Null }));
+            }
             Expression::Variable(variable) => {
                 code.push(IC::instruction(Instruction::Load {
-                    address: self.reference_variable(&*variable.to_string()),
+                    address: self.reference_variable(&variable.to_string()),
                 }));
             }
-            Expression::FunctionCall { func_name, args } => {
-                code.append(&mut self.function_call(&*func_name.to_string(), args));
+            Expression::FunctionCall {
+                func_name,
+                args,
+                position,
+            } => {
+                let call = self.function_call(&func_name.to_string(), args);
+                code.extend(IC::located(*position, call));
             }
-            Expression::Addition { left, right } => {
-                operation!(left, right, Add);
+            Expression::Addition {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Add);
             }
-            Expression::Subtraction { left, right } => {
-                operation!(left, right, Sub);
+            Expression::Subtraction {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Sub);
             }
-            Expression::Multiplication { left, right } => {
-                operation!(left, right, Mul);
+            Expression::Multiplication {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Mul);
             }
-            Expression::Division { left, right } => {
-                operation!(left, right, Div);
+            Expression::Division {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Div);
             }
-            Expression::Modulus { left, right } => {
-                operation!(left, right, Mod);
+            Expression::Modulus {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Mod);
             }
-            Expression::Equal { left, right } => {
-                operation!(left, right, Eq);
+            Expression::Equal {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Eq);
             }
-            Expression::NotEqual { left, right } => {
-                operation!(left, right, Neq);
+            Expression::NotEqual {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Neq);
             }
-            Expression::GreaterThan { left, right } => {
-                operation!(left, right, Gt);
+            Expression::GreaterThan {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Gt);
             }
-            Expression::LessThan { left, right } => {
-                operation!(left, right, Lt);
+            Expression::LessThan {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Lt);
             }
-            Expression::GreaterThanOrEqual { left, right } => {
-                operation!(left, right, Gte);
+            Expression::GreaterThanOrEqual {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Gte);
             }
-            Expression::LessThanOrEqual { left, right } => {
-                operation!(left, right, Lte);
+            Expression::LessThanOrEqual {
+                left,
+                right,
+                position,
+            } => {
+                operation!(left, right, position, Lte);
             }
         }
 
@@ -166,39 +293,53 @@ impl BinLangTranslationUnit {
 
         code.append(&mut self.expression(expression));
         code.push(IC::instruction(Instruction::Store {
-            address: self.reference_variable(&*identifier),
+            address: self.reference_variable(identifier),
         }));
 
         code
     }
-    pub fn function_call(
-        &mut self,
-        func_name: &str,
-        args: &Vec<Expression>,
-    ) -> Vec<IntermediateCode> {
+    pub fn function_call(&mut self, func_name: &str, args: &[Expression]) -> Vec<IntermediateCode> {
         let mut code = vec![];
 
         for arg in args.iter().rev() {
             code.append(&mut self.expression(arg));
         }
 
-        if EXTERNAL_FUNCTIONS.contains(&func_name) {
-            if VARIADIC_FUNCTIONS.contains(&func_name) {
-                code.push(IC::instruction(Instruction::Push {
-                    value: Value::Int(args.len() as i64),
-                }));
+        if let Some(descriptor) = self.registry.get(func_name).copied() {
+            if !descriptor.accepts(args.len()) {
+                self.errors.push(TranslationError::ArgumentCount {
+                    function: func_name.to_string(),
+                    expected: descriptor.arity,
+                    found: args.len(),
+                    variadic: descriptor.variadic,
+                });
             }
 
+            code.push(IC::instruction(Instruction::Push {
+                value: Value::Int(args.len() as i64),
+            }));
             code.push(IC::instruction(Instruction::ExternCall {
                 string_id: self.reference_string(func_name),
             }));
-        } else {
-            for arg_name in self.func_args.get(func_name).unwrap().clone().iter().rev() {
+        } else if let Some(func_args) = self.func_args.get(func_name).cloned() {
+            if func_args.len() != args.len() {
+                self.errors.push(TranslationError::ArgumentCount {
+                    function: func_name.to_string(),
+                    expected: func_args.len(),
+                    found: args.len(),
+                    variadic: false,
+                });
+            }
+
+            for arg_name in func_args.iter().rev() {
                 code.push(IC::instruction(Instruction::Store {
                     address: self.reference_variable(arg_name),
                 }));
             }
             code.push(IC::call(format!("function_{}", func_name).as_str()));
+        } else {
+            self.errors
+                .push(TranslationError::UnknownFunction(func_name.to_string()));
         }
 
         code
@@ -216,34 +357,28 @@ impl BinLangTranslationUnit {
     pub fn conditional(
         &mut self,
         while_scope: usize,
-        bodies: Vec<(Expression, Vec<Statement>)>,
-        else_body: Option<Vec<Statement>>,
+        bodies: Vec<(Expression, Block)>,
+        else_body: Option<Block>,
     ) -> Vec<IntermediateCode> {
         let mut intermediate = vec![];
 
-        let mut if_counts = 0;
         let condition_counts = self.conditional_label_count;
 
         let end_label = format!("end_{}", self.conditional_label_count);
         self.conditional_label_count += 1;
 
-        for (condition, body) in bodies {
+        for (if_counts, (condition, body)) in bodies.into_iter().enumerate() {
             let elif_label = format!("if_{}_{}", condition_counts, if_counts);
-            if_counts += 1;
 
             intermediate.append(&mut self.expression(&condition));
             intermediate.push(IC::jump_if_false(&elif_label));
-            for statement in body {
-                intermediate.append(&mut self.statement(while_scope, &statement));
-            }
+            intermediate.append(&mut self.block(while_scope, &body));
             intermediate.push(IC::jump(&end_label));
             intermediate.push(IC::label(&elif_label));
         }
 
         if let Some(body) = else_body {
-            for statement in body {
-                intermediate.append(&mut self.statement(while_scope, &statement));
-            }
+            intermediate.append(&mut self.block(while_scope, &body));
         } else {
             let label = intermediate.pop().unwrap();
             intermediate.pop().unwrap();
@@ -258,7 +393,7 @@ impl BinLangTranslationUnit {
     pub fn while_loop(
         &mut self,
         condition: &Expression,
-        body: &Vec<Statement>,
+        body: &[(Statement, Span)],
     ) -> Vec<IntermediateCode> {
         let mut intermediate = vec![];
 
@@ -270,29 +405,46 @@ impl BinLangTranslationUnit {
         intermediate.push(IC::label(&condition_label));
         intermediate.append(&mut self.expression(condition));
         intermediate.push(IC::jump_if_false(&end_label));
-
-        for statement in body {
-            intermediate.append(&mut self.statement(count, statement));
-        }
+        intermediate.append(&mut self.block(count, body));
 
         intermediate.push(IC::jump(&condition_label));
         intermediate.push(IC::label(&end_label));
 
         intermediate
     }
-    pub fn statement(&mut self, while_scope: usize, statement: &Statement) -> Vec<IntermediateCode> {
+    /// Every statement of `block`, each located where it starts
+    pub fn block(
+        &mut self,
+        while_scope: usize,
+        block: &[(Statement, Span)],
+    ) -> Vec<IntermediateCode> {
+        let mut intermediate = vec![];
+
+        for (statement, span) in block {
+            let code = self.statement(while_scope, statement);
+            intermediate.extend(IC::located(span.position(), code));
+        }
+
+        intermediate
+    }
+
+    pub fn statement(
+        &mut self,
+        while_scope: usize,
+        statement: &Statement,
+    ) -> Vec<IntermediateCode> {
         let mut intermediate = vec![];
         match statement {
             Statement::Assignment {
                 identifier,
                 expression,
-            } => intermediate.append(&mut self.assignment(&*identifier.to_string(), expression)),
+            } => intermediate.append(&mut self.assignment(&identifier.to_string(), expression)),
             Statement::Reassignment {
                 identifier,
                 expression,
-            } => intermediate.append(&mut self.assignment(&*identifier.to_string(), expression)),
+            } => intermediate.append(&mut self.assignment(&identifier.to_string(), expression)),
             Statement::FunctionCall { func_name, args } => {
-                intermediate.append(&mut self.function_call(&*func_name.to_string(), args))
+                intermediate.append(&mut self.function_call(&func_name.to_string(), args))
             }
             Statement::FunctionDeclaration {
                 func_name,
@@ -301,7 +453,7 @@ impl BinLangTranslationUnit {
             } => intermediate.append(
                 &mut self.function_declaration(
                     while_scope,
-                    &*func_name.to_string(),
+                    &func_name.to_string(),
                     args.iter()
                         .map(|arg| arg.to_string())
                         .collect::<Vec<String>>()
@@ -309,9 +461,8 @@ impl BinLangTranslationUnit {
                     body,
                 ),
             ),
-            Statement::Conditional { body, else_body } => {
-                intermediate.append(&mut self.conditional(while_scope, body.clone(), else_body.clone()))
-            }
+            Statement::Conditional { body, else_body } => intermediate
+                .append(&mut self.conditional(while_scope, body.clone(), else_body.clone())),
             Statement::WhileLoop { condition, body } => {
                 intermediate.append(&mut self.while_loop(condition, body))
             }
@@ -335,77 +486,143 @@ impl BinLangTranslationUnit {
         &mut self,
         while_scope: usize,
         func_name: &str,
-        args: &Vec<String>,
-        body: &Vec<Statement>,
+        args: &[String],
+        body: &[(Statement, Span)],
     ) -> Vec<IntermediateCode> {
         let mut intermediate = vec![];
 
-        self.func_args.insert(func_name.to_string(), args.clone());
+        self.func_args.insert(func_name.to_string(), args.to_vec());
 
         intermediate.push(IC::label(format!("function_{}", func_name).as_str()));
+        intermediate.append(&mut self.block(while_scope, body));
 
-        for statement in body {
-            intermediate.append(&mut self.statement(while_scope, statement));
-        }
+        // functions without `ibalik` must not fall through into the code after them
+        intermediate.push(IC::instruction(Instruction::Ret));
 
         intermediate
     }
 
-    pub fn run(&mut self) -> Vec<Instruction> {
-        let mut intermediate = vec![IC::jump("_start")];
+    pub fn exports(&mut self) -> HashMap<String, FunctionExport> {
+        let mut exports = HashMap::new();
 
-        for code in self.functions.clone().iter() {
-            intermediate.extend(self.statement(0, code))
+        for (func_name, args) in self.func_args.clone() {
+            let address = self.labels[&format!("function_{}", func_name)];
+            let args = args
+                .iter()
+                .map(|arg| self.reference_variable(arg))
+                .collect();
+
+            exports.insert(func_name, FunctionExport { address, args });
         }
 
-        intermediate.push(IC::label("_start"));
+        exports
+    }
+
+    pub fn run(&mut self) -> Vec<Instruction> {
+        self.labels.clear();
+        self.function_starts.clear();
+        self.entries.clear();
+        self.positions = vec![Some(START)];
+
+        let statements = self.statements.clone();
+        let functions = self.functions.clone();
+
+        let mut code = vec![Instruction::Nop];
+        code.extend(self.emit(vec![], &statements, &functions));
+
+        code
+    }
+
+    /// Translates `functions` and then `statements` into code that starts at the end of the code
+    /// emitted so far with a jump over the functions, `prelude` goes between the two
+    fn emit(
+        &mut self,
+        prelude: Vec<IntermediateCode>,
+        statements: &[(Statement, Span)],
+        functions: &[(Statement, Span)],
+    ) -> Vec<Instruction> {
+        let entry = match self.entries.len() {
+            0 => "_start".to_string(),
+            count => format!("_start_{}", count),
+        };
 
-        for code in self.statements.clone().iter() {
-            intermediate.extend(self.statement(0, code))
+        let mut intermediate = vec![IC::jump(&entry)];
+        intermediate.extend(prelude);
+
+        // declare every function up front so calls can refer to functions declared after them
+        for (function, _) in functions.iter() {
+            if let Statement::FunctionDeclaration {
+                func_name, args, ..
+            } = function
+            {
+                self.func_args.insert(
+                    func_name.to_string(),
+                    args.iter().map(|arg| arg.to_string()).collect(),
+                );
+            }
         }
 
+        intermediate.extend(self.block(0, functions));
+        intermediate.push(IC::label(&entry));
+        intermediate.extend(self.block(0, statements));
         intermediate.push(IC::instruction(Instruction::Nop));
 
-        let mut labels = HashMap::new();
-        let mut counter = 1;
+        self.resolve(IC::located(START, intermediate))
+    }
 
+    /// Gives every label of `intermediate` an address after the code emitted so far, labels
+    /// that are not defined in it keep the address they already had
+    fn resolve(&mut self, intermediate: Vec<IntermediateCode>) -> Vec<Instruction> {
+        let mut counter = self.positions.len();
         let mut new_intermediate = vec![];
-
-        let mut code = vec![Instruction::Nop];
+        let mut locations = vec![];
 
         for instruction in intermediate {
             match instruction {
                 IntermediateCode::Label(name) => {
-                    labels.insert(name.to_string(), counter);
+                    if let Some(func_name) = name.strip_prefix("function_") {
+                        self.function_starts.push((func_name.to_string(), counter));
+                    } else if name.starts_with("_start") {
+                        self.entries.push(counter);
+                    }
+                    self.labels.insert(name, counter);
+                }
+                IntermediateCode::Location(position) => locations.push(position),
+                IntermediateCode::EndLocation => {
+                    locations.pop();
                 }
                 _ => {
                     counter += 1;
                     new_intermediate.push(instruction);
+                    self.positions.push(locations.last().copied());
                 }
             }
         }
 
-        let get_label = |name: &str| *labels.get(name).unwrap();
+        let get_label = |name: &str| *self.labels.get(name).unwrap();
 
+        let mut code = vec![];
         for instruction in new_intermediate {
             match instruction {
-                IntermediateCode::Label(_) => {}
+                IntermediateCode::Label(_)
+                | IntermediateCode::Location(_)
+                | IntermediateCode::EndLocation => {}
                 IntermediateCode::Inst(inst) => {
                     code.push(inst);
                 }
                 IntermediateCode::Jump(name) => {
                     code.push(Instruction::Jump {
-                        address: get_label(&*name),
+                        address: get_label(&name),
                     });
                 }
                 IntermediateCode::JumpIfFalse(name) => {
                     code.push(Instruction::JumpIfFalse {
-                        address: get_label(&*name),
+                        address: get_label(&name),
                     });
                 }
                 IntermediateCode::Call(name) => {
                     code.push(Instruction::Call {
-                        address: get_label(&*name),
+                        address: get_label(&name),
                     });
                 }
             }
@@ -414,12 +631,60 @@ impl BinLangTranslationUnit {
         code
     }
 
-    pub fn translate(statements: Vec<Statement>, functions: Vec<Statement>) -> Program {
-        let mut unit = BinLangTranslationUnit {
+    /// Function names with the instructions between their label and the next function or top
+    /// level code
+    pub fn function_ranges(&self) -> Vec<FunctionRange> {
+        let mut functions = self
+            .function_starts
+            .iter()
+            .map(|(func_name, start)| FunctionRange {
+                name: func_name.clone(),
+                start: *start,
+                end: 0,
+            })
+            .collect::<Vec<FunctionRange>>();
+        functions.sort_by_key(|function| function.start);
+
+        let mut boundaries = functions
+            .iter()
+            .map(|function| function.start)
+            .chain(self.entries.iter().copied())
+            .collect::<Vec<usize>>();
+        boundaries.sort();
+
+        for function in functions.iter_mut() {
+            function.end = boundaries
+                .iter()
+                .copied()
+                .find(|boundary| *boundary > function.start)
+                .unwrap_or(self.positions.len());
+        }
+
+        functions
+    }
+
+    pub fn debug_info(&self, source_file: &str) -> DebugInfo {
+        DebugInfo {
+            source_file: source_file.to_string(),
+            positions: self.positions.clone(),
+            variables: (0..self.variable_refs.len())
+                .map(|index| self.variable_ref_by_index[&index].clone())
+                .collect(),
+            functions: self.function_ranges(),
+        }
+    }
+
+    pub fn new(statements: Block, functions: Block) -> BinLangTranslationUnit {
+        BinLangTranslationUnit {
             statements,
             functions,
-            code: vec![],
             func_args: Default::default(),
+            labels: Default::default(),
+            positions: vec![],
+            registry: NativeRegistry::standard(),
+            errors: vec![],
+            function_starts: vec![],
+            entries: vec![],
             conditional_label_count: 0,
             while_label_count: 0,
 
@@ -428,29 +693,346 @@ impl BinLangTranslationUnit {
 
             variable_refs: Default::default(),
             variable_ref_by_index: Default::default(),
-        };
+        }
+    }
 
-        for str in VARIADIC_FUNCTIONS {
-            unit.reference_string(str);
+    /// Translates the unit's statements and functions into a complete program
+    ///
+    /// Strings and variables already referenced keep their indices, so a unit seeded with the
+    /// tables of an earlier one produces a program that can share its heap.
+    pub fn program(&mut self, source_file: &str) -> Result<Program, Vec<TranslationError>> {
+        let inst = self.run();
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
         }
 
-        for str in EXTERNAL_FUNCTIONS {
-            unit.reference_string(str);
+        Ok(Program {
+            instructions: inst,
+            strings: self.strings(),
+            heap_size: self.variable_refs.len(),
+            object_descriptor: vec![],
+            exports: self.exports(),
+            debug: Some(self.debug_info(source_file)),
+        })
+    }
+
+    fn strings(&self) -> Vec<String> {
+        (0..self.string_refs.len())
+            .map(|index| self.string_ref_by_index[&index].clone())
+            .collect()
+    }
+
+    /// Translates `statements` and `functions` onto the end of `program`, which this unit made
+    /// with `program` or earlier calls to `append`, and returns the address the new top level
+    /// code starts at
+    ///
+    /// The code already in `program` stays where it is, so a runtime running it can take the
+    /// result over with `Runtime::reload` and keep going. Strings and variables keep their
+    /// indices, and a function declared again gets a new body at the end, the entry of its old
+    /// body becomes a jump there so existing callers reach the new one. Nothing changes when
+    /// translating fails.
+    ///
+    /// # Example
+    /// ```
+    /// use bincompiler::parser::BinLangParse;
+    /// use bincompiler::translation::BinLangTranslationUnit;
+    ///
+    /// let (statements, functions) = BinLangParse::data("proseso f() { ibalik 1; }").unwrap();
+    /// let mut unit = BinLangTranslationUnit::new(statements, functions);
+    /// let mut program = unit.program("main.bin").unwrap();
+    ///
+    /// let mut runtime = program.clone().into_runtime();
+    /// runtime.run().unwrap();
+    ///
+    /// let (statements, functions) = BinLangParse::data("proseso f() { ibalik 2; }").unwrap();
+    /// let entry = unit.append(&mut program, statements, functions).unwrap();
+    ///
+    /// runtime.reload(&program);
+    /// runtime.instruction_pointer = entry;
+    /// runtime.run().unwrap();
+    ///
+    /// assert_eq!(runtime.call_function("f", &[]).unwrap().as_int(), Some(2));
+    /// ```
+    pub fn append(
+        &mut self,
+        program: &mut Program,
+        statements: Block,
+        functions: Block,
+    ) -> Result<usize, Vec<TranslationError>> {
+        let snapshot = self.clone();
+
+        // callers of a redeclared function store its arguments where the old body reads them
+        let mut prelude = vec![];
+        let mut relocations = vec![];
+        for (function, _) in functions.iter() {
+            let (func_name, args) = match function {
+                Statement::FunctionDeclaration {
+                    func_name, args, ..
+                } => (func_name.to_string(), args),
+                _ => continue,
+            };
+            let old_args = match self.func_args.get(&func_name) {
+                Some(old_args) => old_args.clone(),
+                None => continue,
+            };
+
+            if old_args.len() != args.len() {
+                self.errors.push(TranslationError::SignatureChanged {
+                    function: func_name.clone(),
+                    expected: old_args.len(),
+                    found: args.len(),
+                });
+            }
+
+            let label = format!("function_{}", func_name);
+            let moved = old_args
+                .iter()
+                .zip(args.iter().map(|arg| arg.to_string()))
+                .filter(|(old, new)| **old != *new)
+                .map(|(old, new)| (self.reference_variable(old), self.reference_variable(&new)))
+                .collect::<Vec<(usize, usize)>>();
+
+            if moved.is_empty() {
+                relocations.push((self.labels[&label], label));
+            } else {
+                let relocation = format!("relocate_{}_{}", func_name, self.entries.len());
+                prelude.push(IC::label(&relocation));
+                for (old, _) in moved.iter() {
+                    prelude.push(IC::instruction(Instruction::Load { address: *old }));
+                }
+                for (_, new) in moved.iter().rev() {
+                    prelude.push(IC::instruction(Instruction::Store { address: *new }));
+                }
+                prelude.push(IC::jump(&label));
+
+                relocations.push((self.labels[&label], relocation));
+            }
         }
 
-        let inst = unit.run();
+        let code = self.emit(prelude, &statements, &functions);
+        if !self.errors.is_empty() {
+            let errors = std::mem::take(&mut self.errors);
+            *self = snapshot;
+            return Err(errors);
+        }
 
-        let mut strings = vec![];
+        for function in functions {
+            let func_name = function_name(&function.0);
+            match self
+                .functions
+                .iter_mut()
+                .find(|(declared, _)| function_name(declared) == func_name)
+            {
+                Some(declared) => *declared = function,
+                None => self.functions.push(function),
+            }
+        }
+        self.statements.extend(statements);
 
-        for i in 0..unit.string_refs.len() {
-            strings.push(unit.string_ref_by_index.get(&i).unwrap().clone());
+        for (address, label) in relocations {
+            program.instructions[address] = Instruction::Jump {
+                address: self.labels[&label],
+            };
+        }
+        program.instructions.extend(code);
+        program.strings = self.strings();
+        program.heap_size = self.variable_refs.len();
+        program.exports = self.exports();
+        if let Some(debug) = program.debug.as_ref() {
+            program.debug = Some(self.debug_info(&debug.source_file));
         }
 
-        Program {
-            instructions: inst,
-            strings,
-            heap_size: unit.variable_refs.len(),
-            object_descriptor: vec![],
+        Ok(*self.entries.last().unwrap())
+    }
+
+    pub fn translate(
+        source_file: &str,
+        statements: Block,
+        functions: Block,
+    ) -> Result<Program, Vec<TranslationError>> {
+        BinLangTranslationUnit::new(statements, functions).program(source_file)
+    }
+}
+
+fn function_name(function: &Statement) -> Option<String> {
+    match function {
+        Statement::FunctionDeclaration { func_name, .. } => Some(func_name.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::BinLangParse;
+    use bincore::executable::runtime::Runtime;
+    use bincore::executable::verifier::verify;
+
+    /// A unit, the program it made and a runtime that ran it, as the REPL keeps them
+    struct Session {
+        unit: BinLangTranslationUnit,
+        program: Program,
+        runtime: Runtime,
+    }
+
+    impl Session {
+        fn new(source: &str) -> Session {
+            let (statements, functions) = BinLangParse::data(source).unwrap();
+            let mut unit = BinLangTranslationUnit::new(statements, functions);
+            let program = unit.program("main.bin").unwrap();
+            verify(&program).unwrap();
+
+            let mut runtime = program.clone().into_runtime();
+            runtime.run().unwrap();
+
+            Session {
+                unit,
+                program,
+                runtime,
+            }
+        }
+
+        fn append(&mut self, source: &str) -> Result<(), Vec<TranslationError>> {
+            let (statements, functions) = BinLangParse::data(source).unwrap();
+            let entry = self.unit.append(&mut self.program, statements, functions)?;
+            verify(&self.program).unwrap();
+
+            self.runtime.reload(&self.program);
+            self.runtime.instruction_pointer = entry;
+            self.runtime.run().unwrap();
+            Ok(())
+        }
+
+        fn variable(&self, name: &str) -> Value {
+            self.runtime.heap[self.unit.variable_refs[name]]
+        }
+
+        fn string(&self, name: &str) -> String {
+            self.runtime.from_value(self.variable(name)).unwrap()
         }
+
+        fn call(&mut self, function: &str, args: &[Value]) -> Option<i64> {
+            self.runtime.call_function(function, args).unwrap().as_int()
+        }
+    }
+
+    #[test]
+    fn appended_code_sees_earlier_variables() {
+        let mut session = Session::new("deklara x = 2;");
+        session.append("deklara y = x * 3;").unwrap();
+        session.append("x = x + y;").unwrap();
+
+        assert_eq!(session.variable("x"), Value::Int(8));
+        assert_eq!(session.variable("y"), Value::Int(6));
+    }
+
+    #[test]
+    fn appended_code_runs_once() {
+        let mut session = Session::new("deklara count = 0;");
+        session.append("count = count + 1;").unwrap();
+        session.append("count = count + 10;").unwrap();
+
+        assert_eq!(session.variable("count"), Value::Int(11));
+    }
+
+    #[test]
+    fn strings_made_before_an_append_survive_its_reload() {
+        let mut session = Session::new("deklara a = \"x\" + \"y\";");
+        session.append("deklara b = \"z\";").unwrap();
+        session.append("deklara c = a + b;").unwrap();
+
+        assert_eq!(session.string("a"), "xy");
+        assert_eq!(session.string("b"), "z");
+        assert_eq!(session.string("c"), "xyz");
+    }
+
+    #[test]
+    fn old_callers_reach_a_redeclared_function() {
+        let mut session = Session::new("proseso f() { ibalik 1; }\nproseso g() { ibalik f(); }");
+        assert_eq!(session.call("g", &[]), Some(1));
+
+        session.append("proseso f() { ibalik 2; }").unwrap();
+
+        assert_eq!(session.call("f", &[]), Some(2));
+        assert_eq!(session.call("g", &[]), Some(2));
+    }
+
+    #[test]
+    fn renamed_arguments_are_moved_for_old_callers() {
+        let mut session =
+            Session::new("proseso f(a, b) { ibalik a - b; }\nproseso g() { ibalik f(5, 1); }");
+        let before = session.call("g", &[]).unwrap();
+
+        // `b` was the second argument and is the first now, so it moves as well
+        session
+            .append("proseso f(b, c) { ibalik (b - c) * 10; }")
+            .unwrap();
+
+        assert_eq!(session.call("g", &[]), Some(before * 10));
+        assert_eq!(session.call("f", &[Value::Int(3), Value::Int(1)]), Some(20));
+    }
+
+    #[test]
+    fn calls_and_operators_are_located_where_they_are() {
+        let session = Session::new("proseso g(n) {\n    ibalik n;\n}\ndeklara x = 1 + g(2 * 3);");
+        let instructions = &session.program.instructions;
+        let positions = &session.program.debug.as_ref().unwrap().positions;
+        let at = |instruction: Instruction| {
+            let address = instructions
+                .iter()
+                .position(|candidate| *candidate == instruction)
+                .unwrap();
+            positions[address].map(|position| (position.line, position.column))
+        };
+        let n = session.unit.variable_refs["n"];
+        let x = session.unit.variable_refs["x"];
+
+        assert_eq!(positions.len(), instructions.len());
+        assert!(positions.iter().all(|position| position.is_some()));
+        assert_eq!(positions[0], Some(Position { line: 1, column: 1 }));
+
+        assert_eq!(at(Instruction::Mul), Some((4, 21)));
+        assert_eq!(at(Instruction::Store { address: n }), Some((4, 17)));
+        assert_eq!(at(Instruction::Add), Some((4, 15)));
+        assert_eq!(at(Instruction::Store { address: x }), Some((4, 1)));
+        assert_eq!(at(Instruction::Ret), Some((2, 5)));
+    }
+
+    #[test]
+    fn wala_is_the_null_value() {
+        let session =
+            Session::new("deklara x = wala;\ndeklara walay = x == wala;\ndeklara may = 1 != wala;");
+
+        assert_eq!(session.variable("x"), Value::Null);
+        assert_eq!(session.variable("walay"), Value::Bool(true));
+        assert_eq!(session.variable("may"), Value::Bool(true));
+    }
+
+    #[test]
+    fn failed_appends_change_nothing() {
+        let mut session = Session::new("proseso f(a) { ibalik a; }\ndeklara x = 1;");
+        let program = session.program.clone();
+
+        let errors = session.append("proseso f() { ibalik 0; }").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [TranslationError::SignatureChanged {
+                expected: 1,
+                found: 0,
+                ..
+            }]
+        ));
+
+        let errors = session.append("deklara y = 1; walay(y);").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [TranslationError::UnknownFunction(name)] if name == "walay"
+        ));
+
+        assert_eq!(session.program, program);
+        assert!(!session.unit.variable_refs.contains_key("y"));
+
+        session.append("deklara y = f(x + 1);").unwrap();
+        assert_eq!(session.variable("y"), Value::Int(2));
     }
 }
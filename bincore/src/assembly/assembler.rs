@@ -0,0 +1,692 @@
+use crate::data::function::FunctionExport;
+use crate::data::object::ObjectDescriptor;
+use crate::data::program_file::Program;
+use crate::data::value::Value;
+use crate::executable::runnable::Instruction;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for AssemblyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Char(char),
+}
+
+/// An instruction whose jump or call target may still be a label
+enum Pending {
+    Ready(Instruction),
+    Target {
+        mnemonic: String,
+        label: String,
+        line: usize,
+    },
+}
+
+/// Turns assembly text into a `Program`, the inverse of `disassemble`
+///
+/// The text starts with directives, followed by `.code` and one instruction per line:
+///
+/// * `.heap <size>`, when left out the heap is sized to the highest slot used
+/// * `.string [id] "text"`, string constants in id order
+/// * `.object [id] "Name" "member" ...`, object descriptors in id order
+/// * `.export "name" <label> <argument slot> ...`, functions a host can call
+/// * `<label>:` names the address of the next instruction
+///
+/// Operands are written as the disassembler does (`push int 1`, `push str 0`, `jump loop`), on
+/// top of that `push str "text"` and `extern_call "name"` add the string to the string table.
+/// `;` starts a comment. `Runtime::run` starts at address 1, so code usually starts with `nop`.
+///
+/// # Example
+/// ```
+/// use bincore::assembly::assembler::assemble;
+/// use bincore::assembly::disassembler::disassemble;
+///
+/// let program = assemble(
+///     r#"
+///     .code
+///         nop
+///     loop:
+///         push str "Kumusta"
+///         push int 1
+///         extern_call "ipakita"
+///         jump loop
+///     "#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(program.strings, vec!["Kumusta", "ipakita"]);
+/// assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+/// ```
+pub fn assemble(text: &str) -> Result<Program, Vec<AssemblyError>> {
+    let mut assembler = Assembler::default();
+
+    for (index, line) in text.lines().enumerate() {
+        if let Err(message) = assembler.line(line) {
+            assembler.errors.push(AssemblyError {
+                line: index + 1,
+                message,
+            });
+        }
+    }
+
+    assembler.finish()
+}
+
+#[derive(Default)]
+struct Assembler {
+    heap_size: Option<usize>,
+    strings: Vec<String>,
+    object_descriptor: Vec<ObjectDescriptor>,
+    exports: Vec<(String, String, Vec<usize>, usize)>,
+
+    in_code: bool,
+    line: usize,
+    labels: HashMap<String, usize>,
+    instructions: Vec<Pending>,
+    errors: Vec<AssemblyError>,
+}
+
+impl Assembler {
+    fn line(&mut self, line: &str) -> Result<(), String> {
+        self.line += 1;
+        let mut tokens = tokenize(line)?;
+
+        // a label can share its line with an instruction
+        if let Some(Token::Word(word)) = tokens.first() {
+            if let Some(label) = word.strip_suffix(':') {
+                if !self.in_code {
+                    return Err(format!("label '{}' outside of .code", label));
+                }
+                if self
+                    .labels
+                    .insert(label.to_string(), self.instructions.len())
+                    .is_some()
+                {
+                    return Err(format!("label '{}' is defined twice", label));
+                }
+                tokens.remove(0);
+            }
+        }
+
+        let mut tokens = tokens.into_iter();
+        let first = match tokens.next() {
+            Some(Token::Word(word)) => word,
+            Some(token) => return Err(format!("unexpected {:?}", token)),
+            None => return Ok(()),
+        };
+        let operands = tokens.collect::<Vec<Token>>();
+
+        match first.as_str() {
+            ".heap" => {
+                self.heap_size = Some(number(single(&operands)?)?);
+                Ok(())
+            }
+            ".string" => {
+                let (id, rest) = numbered(&operands, self.strings.len())?;
+                match rest {
+                    [Token::Str(string)] => {
+                        self.check_id(id, self.strings.len())?;
+                        self.strings.push(string.clone());
+                        Ok(())
+                    }
+                    _ => Err(".string expects a quoted string".to_string()),
+                }
+            }
+            ".object" => {
+                let (id, rest) = numbered(&operands, self.object_descriptor.len())?;
+                self.check_id(id, self.object_descriptor.len())?;
+
+                let mut names = rest.iter().map(name);
+                let descriptor_name = match names.next() {
+                    Some(descriptor_name) => descriptor_name?,
+                    None => return Err(".object expects a name".to_string()),
+                };
+                let members = names.collect::<Result<Vec<String>, String>>()?;
+
+                self.object_descriptor
+                    .push(ObjectDescriptor::new(&descriptor_name, members));
+                Ok(())
+            }
+            ".export" => match operands.as_slice() {
+                [function, label, slots @ ..] => {
+                    let slots = slots
+                        .iter()
+                        .map(number)
+                        .collect::<Result<Vec<usize>, String>>()?;
+                    self.exports
+                        .push((name(function)?, name(label)?, slots, self.line));
+                    Ok(())
+                }
+                _ => Err(".export expects a name and a label".to_string()),
+            },
+            ".code" => {
+                self.in_code = true;
+                Ok(())
+            }
+            directive if directive.starts_with('.') => {
+                Err(format!("unknown directive '{}'", directive))
+            }
+            _ if !self.in_code => Err("instructions have to follow .code".to_string()),
+            mnemonic => {
+                let instruction = self.instruction(mnemonic, &operands)?;
+                self.instructions.push(instruction);
+                Ok(())
+            }
+        }
+    }
+
+    fn check_id(&self, id: usize, next: usize) -> Result<(), String> {
+        if id != next {
+            return Err(format!("expected id {}, got {}", next, id));
+        }
+        Ok(())
+    }
+
+    /// Index of `string` in the string table, added when missing
+    fn intern(&mut self, string: &str) -> usize {
+        match self.strings.iter().position(|existing| existing == string) {
+            Some(id) => id,
+            None => {
+                self.strings.push(string.to_string());
+                self.strings.len() - 1
+            }
+        }
+    }
+
+    fn instruction(&mut self, mnemonic: &str, operands: &[Token]) -> Result<Pending, String> {
+        let none = |instruction: Instruction| {
+            if operands.is_empty() {
+                Ok(Pending::Ready(instruction))
+            } else {
+                Err(format!("{} takes no operands", mnemonic))
+            }
+        };
+
+        match mnemonic {
+            "nop" => none(Instruction::Nop),
+            "add" => none(Instruction::Add),
+            "sub" => none(Instruction::Sub),
+            "mul" => none(Instruction::Mul),
+            "div" => none(Instruction::Div),
+            "mod" => none(Instruction::Mod),
+            "pow" => none(Instruction::Pow),
+            "ret" => none(Instruction::Ret),
+            "gt" => none(Instruction::Gt),
+            "lt" => none(Instruction::Lt),
+            "gte" => none(Instruction::Gte),
+            "lte" => none(Instruction::Lte),
+            "eq" => none(Instruction::Eq),
+            "neq" => none(Instruction::Neq),
+            "and" => none(Instruction::And),
+            "or" => none(Instruction::Or),
+            "not" => none(Instruction::Not),
+            "push" => Ok(Pending::Ready(Instruction::Push {
+                value: self.value(operands)?,
+            })),
+            "extern_call" => {
+                let string_id = match single(operands)? {
+                    Token::Str(function) => self.intern(function),
+                    token => number(token)?,
+                };
+                Ok(Pending::Ready(Instruction::ExternCall { string_id }))
+            }
+            "store" => Ok(Pending::Ready(Instruction::Store {
+                address: number(single(operands)?)?,
+            })),
+            "load" => Ok(Pending::Ready(Instruction::Load {
+                address: number(single(operands)?)?,
+            })),
+            "access_member" => Ok(Pending::Ready(Instruction::AccessMember {
+                index: number(single(operands)?)?,
+            })),
+            "set_member" => Ok(Pending::Ready(Instruction::SetMember {
+                index: number(single(operands)?)?,
+            })),
+            "create_object" => Ok(Pending::Ready(Instruction::CreateObject {
+                descriptor: number(single(operands)?)?,
+            })),
+            "call" | "jump" | "jump_if_true" | "jump_if_false" => Ok(Pending::Target {
+                mnemonic: mnemonic.to_string(),
+                label: name(single(operands)?)?,
+                line: self.line,
+            }),
+            _ => Err(format!("unknown instruction '{}'", mnemonic)),
+        }
+    }
+
+    fn value(&mut self, operands: &[Token]) -> Result<Value, String> {
+        let word = |token: &Token| match token {
+            Token::Word(word) => Ok(word.clone()),
+            token => Err(format!("expected a number, got {:?}", token)),
+        };
+
+        match operands {
+            [Token::Word(kind)] if kind == "null" => Ok(Value::Null),
+            [Token::Word(kind), operand] => match (kind.as_str(), operand) {
+                ("int", operand) => {
+                    let word = word(operand)?;
+                    word.parse()
+                        .map(Value::Int)
+                        .map_err(|_| format!("invalid int '{}'", word))
+                }
+                ("float", operand) => {
+                    let word = word(operand)?;
+                    word.parse()
+                        .map(Value::Float)
+                        .map_err(|_| format!("invalid float '{}'", word))
+                }
+                ("bool", Token::Word(value)) if value == "true" => Ok(Value::Bool(true)),
+                ("bool", Token::Word(value)) if value == "false" => Ok(Value::Bool(false)),
+                ("char", Token::Char(char)) => Ok(Value::Char(*char)),
+                ("str", Token::Str(string)) => Ok(Value::StrRef(self.intern(string))),
+                ("str", operand) => Ok(Value::StrRef(number(operand)?)),
+                ("list", operand) => Ok(Value::ListRef(number(operand)?)),
+                ("object", operand) => Ok(Value::ObjectRef(number(operand)?)),
+                _ => Err(format!("invalid {} constant {:?}", kind, operand)),
+            },
+            _ => Err("push expects a type and a value, e.g. `push int 1`".to_string()),
+        }
+    }
+
+    fn finish(mut self) -> Result<Program, Vec<AssemblyError>> {
+        let mut instructions = vec![];
+        for pending in self.instructions.iter() {
+            match pending {
+                Pending::Ready(instruction) => instructions.push(*instruction),
+                Pending::Target {
+                    mnemonic,
+                    label,
+                    line,
+                } => {
+                    let address = match self.labels.get(label) {
+                        Some(address) => *address,
+                        None => match label.parse::<usize>() {
+                            Ok(address) => address,
+                            Err(_) => {
+                                self.errors.push(AssemblyError {
+                                    line: *line,
+                                    message: format!("unknown label '{}'", label),
+                                });
+                                0
+                            }
+                        },
+                    };
+
+                    instructions.push(match mnemonic.as_str() {
+                        "call" => Instruction::Call { address },
+                        "jump" => Instruction::Jump { address },
+                        "jump_if_true" => Instruction::JumpIfTrue { address },
+                        _ => Instruction::JumpIfFalse { address },
+                    });
+                }
+            }
+        }
+
+        let mut exports = HashMap::new();
+        for (function, label, args, line) in self.exports.iter() {
+            match self.labels.get(label) {
+                Some(address) => {
+                    exports.insert(
+                        function.clone(),
+                        FunctionExport {
+                            address: *address,
+                            args: args.clone(),
+                        },
+                    );
+                }
+                None => self.errors.push(AssemblyError {
+                    line: *line,
+                    message: format!("unknown label '{}'", label),
+                }),
+            }
+        }
+
+        if !self.errors.is_empty() {
+            self.errors.sort_by_key(|error| error.line);
+            return Err(self.errors);
+        }
+
+        let heap_size =
+            self.heap_size.unwrap_or_else(|| {
+                instructions
+                    .iter()
+                    .filter_map(|instruction| match instruction {
+                        Instruction::Load { address } | Instruction::Store { address } => {
+                            Some(address + 1)
+                        }
+                        _ => None,
+                    })
+                    .chain(exports.values().flat_map(|export| {
+                        export.args.iter().map(|slot| slot + 1).collect::<Vec<_>>()
+                    }))
+                    .max()
+                    .unwrap_or(0)
+            });
+
+        Ok(Program {
+            instructions,
+            strings: self.strings,
+            heap_size,
+            object_descriptor: self.object_descriptor,
+            exports,
+            debug: None,
+        })
+    }
+}
+
+fn single(operands: &[Token]) -> Result<&Token, String> {
+    match operands {
+        [operand] => Ok(operand),
+        _ => Err(format!("expected one operand, got {}", operands.len())),
+    }
+}
+
+fn number(token: &Token) -> Result<usize, String> {
+    match token {
+        Token::Word(word) => word
+            .parse()
+            .map_err(|_| format!("expected a number, got '{}'", word)),
+        token => Err(format!("expected a number, got {:?}", token)),
+    }
+}
+
+fn name(token: &Token) -> Result<String, String> {
+    match token {
+        Token::Word(word) | Token::Str(word) => Ok(word.clone()),
+        token => Err(format!("expected a name, got {:?}", token)),
+    }
+}
+
+/// An optional leading id, defaulting to `next`
+fn numbered(operands: &[Token], next: usize) -> Result<(usize, &[Token]), String> {
+    match operands.first() {
+        Some(Token::Word(word)) if word.chars().all(|char| char.is_ascii_digit()) => {
+            Ok((number(&operands[0])?, &operands[1..]))
+        }
+        _ => Ok((next, operands)),
+    }
+}
+
+/// Splits a line into words, quoted strings and char literals, dropping the `;` comment
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            ';' => break,
+            char if char.is_whitespace() => {}
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => string.push(escape(&mut chars)?),
+                        Some(char) => string.push(char),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            '\'' => {
+                let value = match chars.next() {
+                    Some('\\') => escape(&mut chars)?,
+                    Some(char) => char,
+                    None => return Err("unterminated char".to_string()),
+                };
+                if chars.next() != Some('\'') {
+                    return Err("unterminated char".to_string());
+                }
+                tokens.push(Token::Char(value));
+            }
+            char => {
+                let mut word = char.to_string();
+                while let Some(char) = chars.peek() {
+                    if char.is_whitespace() || *char == ';' {
+                        break;
+                    }
+                    word.push(*char);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// The escapes written by `{:?}` of strings and chars, after the backslash
+fn escape(chars: &mut impl Iterator<Item = char>) -> Result<char, String> {
+    match chars.next() {
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('0') => Ok('\0'),
+        Some('\\') => Ok('\\'),
+        Some('"') => Ok('"'),
+        Some('\'') => Ok('\''),
+        Some('u') => {
+            if chars.next() != Some('{') {
+                return Err("expected '{' after \\u".to_string());
+            }
+            let code = chars.take_while(|char| *char != '}').collect::<String>();
+            u32::from_str_radix(&code, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| format!("invalid unicode escape '{}'", code))
+        }
+        Some(char) => Err(format!("unknown escape '\\{}'", char)),
+        None => Err("unterminated escape".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly::disassembler::disassemble;
+
+    fn errors(text: &str) -> Vec<(usize, String)> {
+        assemble(text)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| (error.line, error.message))
+            .collect()
+    }
+
+    #[test]
+    fn round_trips_every_instruction() {
+        let mut instructions = vec![
+            Instruction::Nop,
+            Instruction::Push {
+                value: Value::Int(-3),
+            },
+            Instruction::Push {
+                value: Value::Float(0.1),
+            },
+            Instruction::Push {
+                value: Value::Float(-2.0),
+            },
+            Instruction::Push {
+                value: Value::Char('\''),
+            },
+            Instruction::Push {
+                value: Value::Char('ñ'),
+            },
+            Instruction::Push {
+                value: Value::Bool(false),
+            },
+            Instruction::Push { value: Value::Null },
+            Instruction::Push {
+                value: Value::StrRef(1),
+            },
+            Instruction::Push {
+                value: Value::ListRef(2),
+            },
+            Instruction::Push {
+                value: Value::ObjectRef(3),
+            },
+            Instruction::ExternCall { string_id: 0 },
+            Instruction::Store { address: 1 },
+            Instruction::Load { address: 0 },
+            Instruction::AccessMember { index: 1 },
+            Instruction::SetMember { index: 0 },
+            Instruction::CreateObject { descriptor: 0 },
+            Instruction::Call { address: 2 },
+            Instruction::Jump { address: 0 },
+            Instruction::JumpIfTrue { address: 5 },
+            Instruction::JumpIfFalse { address: 20 },
+        ];
+        instructions.extend([
+            Instruction::Add,
+            Instruction::Sub,
+            Instruction::Mul,
+            Instruction::Div,
+            Instruction::Mod,
+            Instruction::Pow,
+            Instruction::Ret,
+            Instruction::Gt,
+            Instruction::Lt,
+            Instruction::Gte,
+            Instruction::Lte,
+            Instruction::Eq,
+            Instruction::Neq,
+            Instruction::And,
+            Instruction::Or,
+            Instruction::Not,
+        ]);
+
+        let program = Program {
+            instructions,
+            strings: vec![
+                "ipakita".to_string(),
+                "tab\t, \"quote\", back\\slash\nand \u{1F600}".to_string(),
+            ],
+            heap_size: 4,
+            object_descriptor: vec![ObjectDescriptor::new(
+                "Punto",
+                vec!["x".to_string(), "y".to_string()],
+            )],
+            exports: HashMap::from([(
+                "function".to_string(),
+                FunctionExport {
+                    address: 2,
+                    args: vec![3, 1],
+                },
+            )]),
+            debug: None,
+        };
+
+        assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+    }
+
+    #[test]
+    fn interns_strings_written_inline() {
+        let program = assemble(
+            r#"
+            .string "ipakita"
+            .code
+                push str "kumusta"
+                push str "kumusta"
+                extern_call "ipakita"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(program.strings, vec!["ipakita", "kumusta"]);
+        assert_eq!(
+            program.instructions,
+            vec![
+                Instruction::Push {
+                    value: Value::StrRef(1)
+                },
+                Instruction::Push {
+                    value: Value::StrRef(1)
+                },
+                Instruction::ExternCall { string_id: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn heap_size_defaults_to_the_highest_slot() {
+        let program = assemble(".export \"f\" start 4\n.code\nstart:\n    store 2").unwrap();
+        assert_eq!(program.heap_size, 5);
+
+        let program = assemble(".code\n    load 2").unwrap();
+        assert_eq!(program.heap_size, 3);
+    }
+
+    #[test]
+    fn reports_every_error_with_its_line() {
+        assert_eq!(
+            errors(
+                r#"
+                nop
+                .code
+                jump nowhere
+                push int x
+                start:
+                start:
+                frobnicate
+                .string 3 "x"
+                push str "unterminated
+                "#
+            ),
+            vec![
+                (2, "instructions have to follow .code".to_string()),
+                (4, "unknown label 'nowhere'".to_string()),
+                (5, "invalid int 'x'".to_string()),
+                (7, "label 'start' is defined twice".to_string()),
+                (8, "unknown instruction 'frobnicate'".to_string()),
+                (9, "expected id 0, got 3".to_string()),
+                (10, "unterminated string".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_bad_operands() {
+        assert_eq!(
+            errors(
+                r#"
+                .code
+                add 1
+                push int
+                push bool maybe
+                store
+                extern_call 'x'
+                push char "x"
+                push str '\q'
+                "#
+            ),
+            vec![
+                (3, "add takes no operands".to_string()),
+                (
+                    4,
+                    "push expects a type and a value, e.g. `push int 1`".to_string()
+                ),
+                (5, "invalid bool constant Word(\"maybe\")".to_string()),
+                (6, "expected one operand, got 0".to_string()),
+                (7, "expected a number, got Char('x')".to_string()),
+                (8, "invalid char constant Str(\"x\")".to_string()),
+                (9, "unknown escape '\\q'".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use crate::assembly::{mnemonic, target};
+use crate::data::program_file::Program;
+use crate::data::value::Value;
+use crate::executable::runnable::Instruction;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Names of the addresses that are jumped to, called or exported
+///
+/// Exports and functions in the debug info are named after their function, other call targets
+/// `fn_<address>` and jump targets `L<address>`.
+pub fn labels(program: &Program) -> HashMap<usize, String> {
+    let mut labels = HashMap::new();
+
+    let mut exports = program.exports.iter().collect::<Vec<_>>();
+    exports.sort_by_key(|(name, _)| name.as_str());
+    for (name, export) in exports {
+        labels.entry(export.address).or_insert_with(|| name.clone());
+    }
+
+    if let Some(debug) = &program.debug {
+        for function in debug.functions.iter() {
+            if labels.contains_key(&function.start) {
+                continue;
+            }
+            // older bodies of a function declared again keep its name with their address
+            let name = if labels.values().any(|label| *label == function.name) {
+                format!("{}_{}", function.name, function.start)
+            } else {
+                function.name.clone()
+            };
+            labels.insert(function.start, name);
+        }
+    }
+
+    for instruction in program.instructions.iter() {
+        if let Instruction::Call { address } = instruction {
+            labels
+                .entry(*address)
+                .or_insert_with(|| format!("fn_{}", address));
+        }
+    }
+
+    for instruction in program.instructions.iter() {
+        if let Some(address) = target(instruction) {
+            labels
+                .entry(address)
+                .or_insert_with(|| format!("L{}", address));
+        }
+    }
+
+    labels
+}
+
+/// Writes `program` as assembly text, the assembler turns it back into the same program
+///
+/// Debug info only shows up as comments, with the source line in front of the instructions
+/// compiled from it, and is not read back.
+///
+/// # Example
+/// ```
+/// use bincore::assembly::disassembler::disassemble;
+/// use bincore::data::program_file::Program;
+/// use bincore::data::value::Value;
+/// use bincore::executable::runnable::Instruction;
+///
+/// let program = Program {
+///     instructions: vec![
+///         Instruction::Nop,
+///         Instruction::Push { value: Value::StrRef(0) },
+///         Instruction::Push { value: Value::Int(1) },
+///         Instruction::ExternCall { string_id: 1 },
+///     ],
+///     strings: vec!["Kumusta".to_string(), "ipakita".to_string()],
+///     ..Program::default()
+/// };
+///
+/// let text = disassemble(&program);
+/// assert!(text.contains(".string 0 \"Kumusta\""));
+/// assert!(text.contains("extern_call 1"));
+/// ```
+pub fn disassemble(program: &Program) -> String {
+    let labels = labels(program);
+    let functions = program
+        .exports
+        .values()
+        .map(|export| export.address)
+        .chain(
+            program
+                .instructions
+                .iter()
+                .filter_map(|instruction| match instruction {
+                    Instruction::Call { address } => Some(*address),
+                    _ => None,
+                }),
+        )
+        .collect::<Vec<usize>>();
+
+    let mut text = String::new();
+
+    if let Some(debug) = &program.debug {
+        writeln!(text, "; source {}", debug.source_file).unwrap();
+    }
+    writeln!(text, ".heap {}", program.heap_size).unwrap();
+
+    if !program.strings.is_empty() {
+        writeln!(text).unwrap();
+    }
+    for (index, string) in program.strings.iter().enumerate() {
+        writeln!(text, ".string {} {:?}", index, string).unwrap();
+    }
+
+    if !program.object_descriptor.is_empty() {
+        writeln!(text).unwrap();
+    }
+    for (index, descriptor) in program.object_descriptor.iter().enumerate() {
+        write!(text, ".object {} {:?}", index, descriptor.name).unwrap();
+        for member in descriptor.members_by_index.iter() {
+            write!(text, " {:?}", member).unwrap();
+        }
+        writeln!(text).unwrap();
+    }
+
+    let mut exports = program.exports.iter().collect::<Vec<_>>();
+    exports.sort_by_key(|(name, _)| name.as_str());
+    if !exports.is_empty() {
+        writeln!(text).unwrap();
+    }
+    for (name, export) in exports {
+        write!(
+            text,
+            ".export {:?} {}",
+            name,
+            label(&labels, export.address)
+        )
+        .unwrap();
+        for slot in export.args.iter() {
+            write!(text, " {}", slot).unwrap();
+        }
+        writeln!(text).unwrap();
+    }
+
+    writeln!(text).unwrap();
+    writeln!(text, ".code").unwrap();
+
+    let mut line = None;
+    for (address, instruction) in program.instructions.iter().enumerate() {
+        if functions.contains(&address) {
+            writeln!(text).unwrap();
+            writeln!(text, "; function {}", labels[&address]).unwrap();
+        }
+        if let Some(label) = labels.get(&address) {
+            writeln!(text, "{}:", label).unwrap();
+        }
+
+        let position = program
+            .debug
+            .as_ref()
+            .and_then(|debug| debug.position(address));
+        if let Some(position) = position {
+            if line != Some(position.line) {
+                writeln!(text, "    ; line {}", position.line).unwrap();
+                line = Some(position.line);
+            }
+        }
+
+        let (operands, note) = operands(program, &labels, instruction);
+        let code = match operands {
+            Some(operands) => format!("{} {}", mnemonic(instruction), operands),
+            None => mnemonic(instruction).to_string(),
+        };
+
+        match note {
+            Some(note) => writeln!(text, "    {:<28}; {:>4}  {}", code, address, note),
+            None => writeln!(text, "    {:<28}; {:>4}", code, address),
+        }
+        .unwrap();
+    }
+
+    // a jump to the end of the program has no instruction to hang its label on
+    if let Some(label) = labels.get(&program.instructions.len()) {
+        writeln!(text, "{}:", label).unwrap();
+    }
+
+    text
+}
+
+fn label(labels: &HashMap<usize, String>, address: usize) -> String {
+    match labels.get(&address) {
+        Some(label) => label.clone(),
+        None => format!("L{}", address),
+    }
+}
+
+/// The operands of `instruction` and a comment with what they refer to
+fn operands(
+    program: &Program,
+    labels: &HashMap<usize, String>,
+    instruction: &Instruction,
+) -> (Option<String>, Option<String>) {
+    let string = |id: usize| {
+        program
+            .strings
+            .get(id)
+            .map(|string| format!("{:?}", string))
+    };
+
+    match *instruction {
+        Instruction::Push { value } => match value {
+            Value::Int(value) => (Some(format!("int {}", value)), None),
+            Value::Float(value) => (Some(format!("float {:?}", value)), None),
+            Value::Char(value) => (Some(format!("char {:?}", value)), None),
+            Value::Bool(value) => (Some(format!("bool {}", value)), None),
+            Value::Null => (Some("null".to_string()), None),
+            Value::StrRef(id) => (Some(format!("str {}", id)), string(id)),
+            Value::ListRef(id) => (Some(format!("list {}", id)), None),
+            Value::ObjectRef(id) => (Some(format!("object {}", id)), None),
+        },
+        Instruction::ExternCall { string_id } => (Some(string_id.to_string()), string(string_id)),
+        Instruction::Store { address } | Instruction::Load { address } => (
+            Some(address.to_string()),
+            program
+                .debug
+                .as_ref()
+                .and_then(|debug| debug.variable(address))
+                .map(|name| name.to_string()),
+        ),
+        Instruction::AccessMember { index } | Instruction::SetMember { index } => {
+            (Some(index.to_string()), None)
+        }
+        Instruction::Call { address }
+        | Instruction::Jump { address }
+        | Instruction::JumpIfTrue { address }
+        | Instruction::JumpIfFalse { address } => (Some(label(labels, address)), None),
+        Instruction::CreateObject { descriptor } => (
+            Some(descriptor.to_string()),
+            program
+                .object_descriptor
+                .get(descriptor)
+                .map(|descriptor| descriptor.name.clone()),
+        ),
+        _ => (None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly::assembler::assemble;
+    use crate::data::debug_info::{DebugInfo, FunctionRange, Position};
+    use crate::data::function::FunctionExport;
+
+    fn function(name: &str, start: usize, end: usize) -> FunctionRange {
+        FunctionRange {
+            name: name.to_string(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn labels_name_functions_and_jump_targets() {
+        let program = Program {
+            instructions: vec![
+                Instruction::Nop,
+                Instruction::Jump { address: 6 },
+                Instruction::Ret,
+                Instruction::Ret,
+                Instruction::Ret,
+                Instruction::Ret,
+                Instruction::Call { address: 2 },
+                Instruction::Call { address: 3 },
+                Instruction::Call { address: 4 },
+                Instruction::Call { address: 5 },
+            ],
+            exports: HashMap::from([(
+                "exported".to_string(),
+                FunctionExport {
+                    address: 2,
+                    args: vec![],
+                },
+            )]),
+            debug: Some(DebugInfo {
+                functions: vec![
+                    function("exported", 2, 3),
+                    function("square", 3, 4),
+                    function("square", 4, 5),
+                ],
+                ..DebugInfo::default()
+            }),
+            ..Program::default()
+        };
+
+        let labels = labels(&program);
+
+        assert_eq!(labels[&2], "exported");
+        assert_eq!(labels[&3], "square");
+        // a function declared again keeps its old body under a name of its own
+        assert_eq!(labels[&4], "square_4");
+        assert_eq!(labels[&5], "fn_5");
+        assert_eq!(labels[&6], "L6");
+        assert_eq!(labels.len(), 5);
+    }
+
+    #[test]
+    fn debug_info_shows_up_as_comments() {
+        let program = Program {
+            instructions: vec![
+                Instruction::Nop,
+                Instruction::Push {
+                    value: Value::StrRef(0),
+                },
+                Instruction::Store { address: 0 },
+            ],
+            strings: vec!["kumusta".to_string()],
+            heap_size: 1,
+            debug: Some(DebugInfo {
+                source_file: "main.bin".to_string(),
+                positions: vec![None, Some(Position { line: 3, column: 1 }), None],
+                variables: vec!["greeting".to_string()],
+                functions: vec![],
+            }),
+            ..Program::default()
+        };
+
+        let text = disassemble(&program);
+
+        assert!(text.starts_with("; source main.bin\n"));
+        assert!(text.contains("    ; line 3\n    push str 0"));
+        assert!(text.contains("\"kumusta\""));
+        assert!(text.contains("store 0                     ;    2  greeting"));
+
+        let assembled = assemble(&text).unwrap();
+        assert_eq!(
+            assembled,
+            Program {
+                debug: None,
+                ..program
+            }
+        );
+    }
+
+    #[test]
+    fn jumps_to_the_end_keep_their_label() {
+        let program = Program {
+            instructions: vec![Instruction::Nop, Instruction::Jump { address: 2 }],
+            ..Program::default()
+        };
+
+        let text = disassemble(&program);
+
+        assert!(text.ends_with("L2:\n"));
+        assert_eq!(assemble(&text).unwrap(), program);
+    }
+}
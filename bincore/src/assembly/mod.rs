@@ -0,0 +1,49 @@
+pub mod assembler;
+pub mod disassembler;
+
+use crate::executable::runnable::Instruction;
+
+/// Name of `instruction` in the assembly text
+pub fn mnemonic(instruction: &Instruction) -> &'static str {
+    match instruction {
+        Instruction::Nop => "nop",
+        Instruction::Push { .. } => "push",
+        Instruction::ExternCall { .. } => "extern_call",
+        Instruction::Store { .. } => "store",
+        Instruction::Load { .. } => "load",
+        Instruction::AccessMember { .. } => "access_member",
+        Instruction::SetMember { .. } => "set_member",
+        Instruction::Add => "add",
+        Instruction::Sub => "sub",
+        Instruction::Mul => "mul",
+        Instruction::Div => "div",
+        Instruction::Mod => "mod",
+        Instruction::Pow => "pow",
+        Instruction::Ret => "ret",
+        Instruction::Gt => "gt",
+        Instruction::Lt => "lt",
+        Instruction::Gte => "gte",
+        Instruction::Lte => "lte",
+        Instruction::Eq => "eq",
+        Instruction::Neq => "neq",
+        Instruction::And => "and",
+        Instruction::Or => "or",
+        Instruction::Not => "not",
+        Instruction::Call { .. } => "call",
+        Instruction::Jump { .. } => "jump",
+        Instruction::JumpIfTrue { .. } => "jump_if_true",
+        Instruction::JumpIfFalse { .. } => "jump_if_false",
+        Instruction::CreateObject { .. } => "create_object",
+    }
+}
+
+/// The address a jump or call goes to
+pub fn target(instruction: &Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::Call { address }
+        | Instruction::Jump { address }
+        | Instruction::JumpIfTrue { address }
+        | Instruction::JumpIfFalse { address } => Some(*address),
+        _ => None,
+    }
+}
@@ -0,0 +1,587 @@
+use crate::data::program_file::Program;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// First bytes of every `.blc` file
+pub const MAGIC: [u8; 4] = *b"BLC\0";
+
+/// Bumped whenever the layout or the encoding of a section changes
+pub const FORMAT_VERSION: u16 = 1;
+
+const SECTION_INSTRUCTIONS: [u8; 4] = *b"INST";
+const SECTION_STRINGS: [u8; 4] = *b"STRS";
+const SECTION_HEAP: [u8; 4] = *b"HEAP";
+const SECTION_DESCRIPTORS: [u8; 4] = *b"DESC";
+const SECTION_EXPORTS: [u8; 4] = *b"EXPT";
+const SECTION_DEBUG: [u8; 4] = *b"DBUG";
+
+/// Everything in front of the sections
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub format_version: u16,
+    pub compiler_version: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContainerError {
+    BadMagic,
+    UnsupportedVersion { found: u16, supported: u16 },
+    Truncated,
+    ChecksumMismatch { expected: u32, found: u32 },
+    MissingSection(String),
+    InvalidSection { section: String, message: String },
+    InvalidJson(String),
+}
+
+impl Display for ContainerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContainerError::BadMagic => write!(
+                f,
+                "Not a compiled Binisaya program, or compiled by an older bincompiler"
+            ),
+            ContainerError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "File format version {} is not supported, this runner reads version {}",
+                found, supported
+            ),
+            ContainerError::Truncated => write!(f, "File is truncated"),
+            ContainerError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Checksum mismatch, expected {:08x} but the contents hash to {:08x}",
+                expected, found
+            ),
+            ContainerError::MissingSection(section) => write!(f, "Missing section {}", section),
+            ContainerError::InvalidSection { section, message } => {
+                write!(f, "Invalid section {}: {}", section, message)
+            }
+            ContainerError::InvalidJson(message) => write!(f, "Invalid JSON program: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {}
+
+/// Writes `program` as a `.blc` file
+///
+/// Layout, integers are little endian:
+/// `MAGIC`, format version `u16`, compiler version (`u16` length + UTF-8), section count `u16`,
+/// then per section a 4 byte tag, a `u32` length and the bincode encoded contents, and finally a
+/// CRC-32 `u32` of every byte before it. The `DBUG` section is only written when the program has
+/// debug info.
+///
+/// # Example
+/// ```
+/// use bincore::data::container;
+/// use bincore::data::program_file::Program;
+///
+/// let program = Program::default();
+/// let bytes = container::encode(&program, "example 1.0");
+/// let (header, decoded) = container::decode(&bytes).unwrap();
+///
+/// assert_eq!(header.compiler_version, "example 1.0");
+/// assert_eq!(decoded.heap_size, program.heap_size);
+/// assert!(container::decode(&bytes[..bytes.len() - 1]).is_err());
+/// ```
+pub fn encode(program: &Program, compiler_version: &str) -> Vec<u8> {
+    let mut sections = vec![
+        (SECTION_INSTRUCTIONS, section(&program.instructions)),
+        (SECTION_STRINGS, section(&program.strings)),
+        (SECTION_HEAP, section(&program.heap_size)),
+        (SECTION_DESCRIPTORS, section(&program.object_descriptor)),
+        (SECTION_EXPORTS, section(&program.exports)),
+    ];
+    if let Some(debug) = &program.debug {
+        sections.push((SECTION_DEBUG, section(debug)));
+    }
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(compiler_version.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(compiler_version.as_bytes());
+
+    bytes.extend_from_slice(&(sections.len() as u16).to_le_bytes());
+    for (tag, contents) in sections.iter() {
+        bytes.extend_from_slice(tag);
+        bytes.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(contents);
+    }
+
+    let checksum = crc32(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+
+    bytes
+}
+
+/// Reads a `.blc` file written by `encode`, sections with unknown tags are skipped
+pub fn decode(bytes: &[u8]) -> Result<(Header, Program), ContainerError> {
+    let header = read_header(bytes)?;
+
+    if bytes.len() < 4 {
+        return Err(ContainerError::Truncated);
+    }
+    let (contents, checksum) = bytes.split_at(bytes.len() - 4);
+    let expected = u32::from_le_bytes(checksum.try_into().unwrap());
+    let found = crc32(contents);
+    if expected != found {
+        return Err(ContainerError::ChecksumMismatch { expected, found });
+    }
+
+    let mut reader = Reader {
+        bytes: contents,
+        position: MAGIC.len() + 2,
+    };
+    let compiler_version_length = reader.u16()? as usize;
+    reader.take(compiler_version_length)?;
+
+    let mut program = Program::default();
+    let mut found_sections = vec![];
+
+    let section_count = reader.u16()?;
+    for _ in 0..section_count {
+        let tag: [u8; 4] = reader.take(4)?.try_into().unwrap();
+        let length = reader.u32()? as usize;
+        let contents = reader.take(length)?;
+
+        match tag {
+            SECTION_INSTRUCTIONS => program.instructions = read_section(&tag, contents)?,
+            SECTION_STRINGS => program.strings = read_section(&tag, contents)?,
+            SECTION_HEAP => program.heap_size = read_section(&tag, contents)?,
+            SECTION_DESCRIPTORS => program.object_descriptor = read_section(&tag, contents)?,
+            SECTION_EXPORTS => program.exports = read_section(&tag, contents)?,
+            SECTION_DEBUG => program.debug = Some(read_section(&tag, contents)?),
+            _ => continue,
+        }
+        found_sections.push(tag);
+    }
+
+    if reader.position != contents.len() {
+        return Err(ContainerError::InvalidSection {
+            section: "table".to_string(),
+            message: "unexpected bytes after the last section".to_string(),
+        });
+    }
+
+    for required in [
+        SECTION_INSTRUCTIONS,
+        SECTION_STRINGS,
+        SECTION_HEAP,
+        SECTION_DESCRIPTORS,
+        SECTION_EXPORTS,
+    ] {
+        if !found_sections.contains(&required) {
+            return Err(ContainerError::MissingSection(tag_name(&required)));
+        }
+    }
+
+    Ok((header, program))
+}
+
+/// Reads only the header, checking the magic bytes and the format version
+pub fn read_header(bytes: &[u8]) -> Result<Header, ContainerError> {
+    let mut reader = Reader { bytes, position: 0 };
+
+    if reader
+        .take(MAGIC.len())
+        .map_err(|_| ContainerError::BadMagic)?
+        != MAGIC
+    {
+        return Err(ContainerError::BadMagic);
+    }
+
+    let format_version = reader.u16()?;
+    if format_version != FORMAT_VERSION {
+        return Err(ContainerError::UnsupportedVersion {
+            found: format_version,
+            supported: FORMAT_VERSION,
+        });
+    }
+
+    let compiler_version_length = reader.u16()? as usize;
+    let compiler_version = String::from_utf8_lossy(reader.take(compiler_version_length)?);
+
+    Ok(Header {
+        format_version,
+        compiler_version: compiler_version.to_string(),
+    })
+}
+
+/// Writes `program` as pretty printed JSON, for reading compiler output and diffing it
+///
+/// Every instruction, string, export and debug position gets a line of its own and object keys
+/// are sorted, so the same program always gives the same text.
+///
+/// # Example
+/// ```
+/// use bincore::data::container;
+/// use bincore::data::program_file::Program;
+/// use bincore::executable::runnable::Instruction;
+///
+/// let program = Program {
+///     instructions: vec![Instruction::Nop, Instruction::Jump { address: 2 }],
+///     ..Program::default()
+/// };
+/// let text = container::encode_json(&program, "example 1.0");
+///
+/// assert!(text.contains("    {\"Jump\":{\"address\":2}}\n"));
+/// assert_eq!(container::load(text.as_bytes()).unwrap().1, program);
+/// ```
+pub fn encode_json(program: &Program, compiler_version: &str) -> String {
+    let debug = match &program.debug {
+        Some(debug) => fields(
+            &[
+                ("source_file", compact(&debug.source_file)),
+                ("variables", lines(&debug.variables, 2)),
+                ("functions", lines(&debug.functions, 2)),
+                ("positions", lines(&debug.positions, 2)),
+            ],
+            1,
+        ),
+        None => "null".to_string(),
+    };
+
+    let mut exports = program.exports.iter().collect::<Vec<_>>();
+    exports.sort_by_key(|(name, _)| name.as_str());
+    let exports = exports
+        .into_iter()
+        .map(|(name, export)| (name.as_str(), compact(export)))
+        .collect::<Vec<_>>();
+
+    let mut text = fields(
+        &[
+            ("format_version", compact(&FORMAT_VERSION)),
+            ("compiler_version", compact(&compiler_version)),
+            ("heap_size", compact(&program.heap_size)),
+            ("strings", lines(&program.strings, 1)),
+            ("object_descriptor", lines(&program.object_descriptor, 1)),
+            ("exports", fields(&exports, 1)),
+            ("instructions", lines(&program.instructions, 1)),
+            ("debug", debug),
+        ],
+        0,
+    );
+    text.push('\n');
+
+    text
+}
+
+/// Reads a program written by `encode_json`
+pub fn decode_json(text: &str) -> Result<(Header, Program), ContainerError> {
+    #[derive(Deserialize)]
+    struct JsonProgram {
+        compiler_version: String,
+        #[serde(flatten)]
+        program: Program,
+    }
+
+    let invalid = |error: serde_json::Error| ContainerError::InvalidJson(error.to_string());
+
+    let value: serde_json::Value = serde_json::from_str(text).map_err(invalid)?;
+    let format_version = value
+        .get("format_version")
+        .and_then(|version| version.as_u64())
+        .ok_or_else(|| ContainerError::InvalidJson("missing format_version".to_string()))?;
+    if format_version != FORMAT_VERSION as u64 {
+        return Err(ContainerError::UnsupportedVersion {
+            found: format_version.min(u16::MAX as u64) as u16,
+            supported: FORMAT_VERSION,
+        });
+    }
+
+    let json: JsonProgram = serde_json::from_value(value).map_err(invalid)?;
+    let header = Header {
+        format_version: FORMAT_VERSION,
+        compiler_version: json.compiler_version,
+    };
+
+    Ok((header, json.program))
+}
+
+/// Reads a program in either form, JSON is recognized by its leading `{`
+pub fn load(bytes: &[u8]) -> Result<(Header, Program), ContainerError> {
+    match bytes.iter().find(|byte| !byte.is_ascii_whitespace()) {
+        Some(b'{') => match std::str::from_utf8(bytes) {
+            Ok(text) => decode_json(text),
+            Err(error) => Err(ContainerError::InvalidJson(error.to_string())),
+        },
+        _ => decode(bytes),
+    }
+}
+
+/// Single line JSON, going through `serde_json::Value` sorts the keys of maps
+fn compact<T: Serialize + ?Sized>(value: &T) -> String {
+    let value = serde_json::to_value(value).expect("programs are always serializable as JSON");
+    value.to_string()
+}
+
+fn lines<T: Serialize>(items: &[T], depth: usize) -> String {
+    if items.is_empty() {
+        return "[]".to_string();
+    }
+
+    let indent = "  ".repeat(depth);
+    let items = items
+        .iter()
+        .map(|item| format!("{}  {}", indent, compact(item)))
+        .collect::<Vec<String>>();
+
+    format!("[\n{}\n{}]", items.join(",\n"), indent)
+}
+
+fn fields(fields: &[(&str, String)], depth: usize) -> String {
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+
+    let indent = "  ".repeat(depth);
+    let fields = fields
+        .iter()
+        .map(|(name, value)| format!("{}  {}: {}", indent, compact(name), value))
+        .collect::<Vec<String>>();
+
+    format!("{{\n{}\n{}}}", fields.join(",\n"), indent)
+}
+
+fn section<T: Serialize>(value: &T) -> Vec<u8> {
+    bincode::serialize(value).expect("program sections are always serializable")
+}
+
+fn read_section<T: DeserializeOwned>(tag: &[u8; 4], contents: &[u8]) -> Result<T, ContainerError> {
+    bincode::deserialize(contents).map_err(|error| ContainerError::InvalidSection {
+        section: tag_name(tag),
+        message: error.to_string(),
+    })
+}
+
+fn tag_name(tag: &[u8; 4]) -> String {
+    String::from_utf8_lossy(tag).to_string()
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], ContainerError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .ok_or(ContainerError::Truncated)?;
+        let bytes = self
+            .bytes
+            .get(self.position..end)
+            .ok_or(ContainerError::Truncated)?;
+
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, ContainerError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, ContainerError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+/// CRC-32 as used by zip and png
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::debug_info::DebugInfo;
+    use crate::executable::runnable::Instruction;
+
+    fn program() -> Program {
+        Program {
+            instructions: vec![Instruction::Nop, Instruction::Jump { address: 2 }],
+            strings: vec!["kumusta".to_string()],
+            heap_size: 3,
+            debug: Some(DebugInfo::default()),
+            ..Program::default()
+        }
+    }
+
+    /// A container like `encode` writes, with only the sections given
+    fn container(sections: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&(sections.len() as u16).to_le_bytes());
+        for (tag, contents) in sections {
+            bytes.extend_from_slice(tag);
+            bytes.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(contents);
+        }
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+
+        bytes
+    }
+
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn decode_reads_what_encode_wrote() {
+        let (header, decoded) = decode(&encode(&program(), "test 1.0")).unwrap();
+
+        assert_eq!(header.format_version, FORMAT_VERSION);
+        assert_eq!(header.compiler_version, "test 1.0");
+        assert_eq!(decoded, program());
+    }
+
+    #[test]
+    fn decode_rejects_other_files() {
+        assert_eq!(decode(b"BL"), Err(ContainerError::BadMagic));
+        assert_eq!(decode(b"{\"heap_size\": 0}"), Err(ContainerError::BadMagic));
+    }
+
+    #[test]
+    fn decode_rejects_other_versions() {
+        let mut bytes = encode(&program(), "test 1.0");
+        bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+
+        assert_eq!(
+            decode(&bytes),
+            Err(ContainerError::UnsupportedVersion {
+                found: FORMAT_VERSION + 1,
+                supported: FORMAT_VERSION,
+            })
+        );
+    }
+
+    #[test]
+    fn decode_rejects_changed_contents() {
+        let mut bytes = encode(&program(), "test 1.0");
+        let last = bytes.len() - 5;
+        bytes[last] ^= 0xFF;
+
+        assert!(matches!(
+            decode(&bytes),
+            Err(ContainerError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn decode_rejects_truncated_files() {
+        let bytes = encode(&program(), "test 1.0");
+        assert_eq!(decode(&bytes[..7]), Err(ContainerError::Truncated));
+
+        // a section claiming more bytes than follow it, behind a valid checksum
+        let mut bytes = container(&[]);
+        bytes.truncate(bytes.len() - 4);
+        bytes[MAGIC.len() + 4..MAGIC.len() + 6].copy_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&SECTION_HEAP);
+        bytes.extend_from_slice(&100u32.to_le_bytes());
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        assert_eq!(decode(&bytes), Err(ContainerError::Truncated));
+    }
+
+    #[test]
+    fn decode_requires_every_section_but_debug() {
+        let program = program();
+        let mut sections = vec![
+            (SECTION_INSTRUCTIONS, section(&program.instructions)),
+            (SECTION_STRINGS, section(&program.strings)),
+            (SECTION_HEAP, section(&program.heap_size)),
+            (SECTION_DESCRIPTORS, section(&program.object_descriptor)),
+            (SECTION_EXPORTS, section(&program.exports)),
+        ];
+        let (_, decoded) = decode(&container(&sections)).unwrap();
+        assert_eq!(decoded.debug, None);
+
+        sections.remove(1);
+        assert_eq!(
+            decode(&container(&sections)),
+            Err(ContainerError::MissingSection("STRS".to_string()))
+        );
+    }
+
+    #[test]
+    fn decode_skips_unknown_sections() {
+        let program = Program::default();
+        let sections = vec![
+            (*b"NEW!", vec![1, 2, 3]),
+            (SECTION_INSTRUCTIONS, section(&program.instructions)),
+            (SECTION_STRINGS, section(&program.strings)),
+            (SECTION_HEAP, section(&program.heap_size)),
+            (SECTION_DESCRIPTORS, section(&program.object_descriptor)),
+            (SECTION_EXPORTS, section(&program.exports)),
+        ];
+
+        assert_eq!(decode(&container(&sections)).unwrap().1, program);
+    }
+
+    #[test]
+    fn decode_rejects_invalid_sections() {
+        let sections = vec![(SECTION_HEAP, vec![1])];
+
+        assert!(matches!(
+            decode(&container(&sections)),
+            Err(ContainerError::InvalidSection { section, .. }) if section == "HEAP"
+        ));
+    }
+
+    #[test]
+    fn load_reads_both_forms() {
+        let json = encode_json(&program(), "test 1.0");
+        let binary = encode(&program(), "test 1.0");
+
+        assert_eq!(load(json.as_bytes()).unwrap().1, program());
+        assert_eq!(load(&binary).unwrap().1, program());
+    }
+
+    #[test]
+    fn decode_json_checks_the_version() {
+        let text = encode_json(&program(), "test 1.0").replace(
+            &format!("\"format_version\": {}", FORMAT_VERSION),
+            "\"format_version\": 99",
+        );
+
+        assert_eq!(
+            decode_json(&text),
+            Err(ContainerError::UnsupportedVersion {
+                found: 99,
+                supported: FORMAT_VERSION,
+            })
+        );
+        assert!(matches!(
+            decode_json("{}"),
+            Err(ContainerError::InvalidJson(_))
+        ));
+    }
+
+    #[test]
+    fn encode_json_puts_every_entry_on_a_line_of_its_own() {
+        let mut program = program();
+        program.strings.push("quote \" and\nnewline".to_string());
+        let text = encode_json(&program, "test 1.0");
+
+        assert!(text.contains("  \"strings\": [\n    \"kumusta\",\n"));
+        assert!(text.contains("    \"quote \\\" and\\nnewline\"\n  ],"));
+        assert!(text.contains("  \"exports\": {},\n"));
+        assert!(text.contains("  \"debug\": {\n    \"source_file\": \"\",\n"));
+        assert_eq!(load(text.as_bytes()).unwrap().1, program);
+
+        program.debug = None;
+        assert!(encode_json(&program, "test 1.0").ends_with("  \"debug\": null\n}\n"));
+    }
+}
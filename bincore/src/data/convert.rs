@@ -0,0 +1,233 @@
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
+use crate::executable::runtime::Runtime;
+
+/// Conversion of a Rust value into a `Value`, strings and lists are allocated on the runtime
+pub trait IntoValue {
+    fn into_value(self, runtime: &mut Runtime) -> Value;
+}
+
+/// Conversion of a `Value` back into a Rust value, following string and list references
+pub trait FromValue: Sized {
+    fn from_value(value: Value, runtime: &Runtime) -> Result<Self, RuntimeError>;
+}
+
+/// A list argument taken by reference, so natives can modify the list in place
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ListRef(pub usize);
+
+/// An int or a float argument, for natives that accept both
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    pub fn as_float(self) -> f64 {
+        match self {
+            Number::Int(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
+}
+
+fn mismatch<T>(expected: &'static str, found: Value) -> Result<T, RuntimeError> {
+    Err(RuntimeError::TypeMismatch { expected, found })
+}
+
+impl IntoValue for Value {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        self
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value, _: &Runtime) -> Result<Self, RuntimeError> {
+        Ok(value)
+    }
+}
+
+impl FromValue for () {
+    fn from_value(value: Value, _: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::Null => Ok(()),
+            _ => mismatch("null", value),
+        }
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        Value::Int(self)
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value, _: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::Int(value) => Ok(value),
+            _ => mismatch("int", value),
+        }
+    }
+}
+
+impl IntoValue for i32 {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        Value::Int(self as i64)
+    }
+}
+
+impl IntoValue for usize {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        Value::Int(self as i64)
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        Value::Float(self)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value, _: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::Float(value) => Ok(value),
+            _ => mismatch("float", value),
+        }
+    }
+}
+
+impl IntoValue for Number {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        match self {
+            Number::Int(value) => Value::Int(value),
+            Number::Float(value) => Value::Float(value),
+        }
+    }
+}
+
+impl FromValue for Number {
+    fn from_value(value: Value, _: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::Int(value) => Ok(Number::Int(value)),
+            Value::Float(value) => Ok(Number::Float(value)),
+            _ => mismatch("number", value),
+        }
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        Value::Bool(self)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value, _: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::Bool(value) => Ok(value),
+            _ => mismatch("bool", value),
+        }
+    }
+}
+
+impl IntoValue for char {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        Value::Char(self)
+    }
+}
+
+impl FromValue for char {
+    fn from_value(value: Value, _: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::Char(value) => Ok(value),
+            _ => mismatch("char", value),
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self, runtime: &mut Runtime) -> Value {
+        runtime.new_string(self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self, runtime: &mut Runtime) -> Value {
+        runtime.new_string(self.to_string())
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value, runtime: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::StrRef(string) => match runtime.string_objects.get(&string) {
+                Some(string) => Ok(string.clone()),
+                None => Err(RuntimeError::InvalidReference(value)),
+            },
+            Value::Char(char) => Ok(char.to_string()),
+            _ => mismatch("string", value),
+        }
+    }
+}
+
+impl IntoValue for ListRef {
+    fn into_value(self, _: &mut Runtime) -> Value {
+        Value::ListRef(self.0)
+    }
+}
+
+impl FromValue for ListRef {
+    fn from_value(value: Value, runtime: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::ListRef(list) if runtime.lists.contains_key(&list) => Ok(ListRef(list)),
+            Value::ListRef(_) => Err(RuntimeError::InvalidReference(value)),
+            _ => mismatch("list", value),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self, runtime: &mut Runtime) -> Value {
+        let values = self
+            .into_iter()
+            .map(|value| value.into_value(runtime))
+            .collect();
+        runtime.new_list(values)
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value, runtime: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::ListRef(list) => match runtime.lists.get(&list) {
+                Some(list) => list
+                    .iter()
+                    .map(|value| T::from_value(*value, runtime))
+                    .collect(),
+                None => Err(RuntimeError::InvalidReference(value)),
+            },
+            _ => mismatch("list", value),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self, runtime: &mut Runtime) -> Value {
+        match self {
+            Some(value) => value.into_value(runtime),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value, runtime: &Runtime) -> Result<Self, RuntimeError> {
+        match value {
+            Value::Null => Ok(None),
+            _ => T::from_value(value, runtime).map(Some),
+        }
+    }
+}
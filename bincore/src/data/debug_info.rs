@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Line and column in the source file, both starting at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Instructions `start..end` belong to the function `name`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FunctionRange {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Names and source positions the compiler knew about, written to the optional `DBUG` section
+///
+/// # Example
+/// ```
+/// use bincore::data::debug_info::{DebugInfo, FunctionRange, Position};
+///
+/// let debug = DebugInfo {
+///     source_file: "main.bin".to_string(),
+///     positions: vec![None, Some(Position { line: 2, column: 5 }), None],
+///     variables: vec!["x".to_string()],
+///     functions: vec![FunctionRange { name: "square".to_string(), start: 1, end: 2 }],
+/// };
+///
+/// assert_eq!(debug.describe(1), "main.bin:2:5 in square");
+/// assert_eq!(debug.describe(2), "main.bin");
+/// assert_eq!(debug.variable(0), Some("x"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct DebugInfo {
+    pub source_file: String,
+    /// source position of the statement, call or operator each instruction was compiled from, by
+    /// address
+    pub positions: Vec<Option<Position>>,
+    /// variable name of each heap slot
+    pub variables: Vec<String>,
+    pub functions: Vec<FunctionRange>,
+}
+
+impl DebugInfo {
+    pub fn position(&self, address: usize) -> Option<Position> {
+        self.positions.get(address).copied().flatten()
+    }
+
+    pub fn variable(&self, slot: usize) -> Option<&str> {
+        self.variables.get(slot).map(|name| name.as_str())
+    }
+
+    /// The function `address` lies in, `None` for top level code
+    pub fn function(&self, address: usize) -> Option<&FunctionRange> {
+        self.functions
+            .iter()
+            .find(|function| function.start <= address && address < function.end)
+    }
+
+    /// `file:line:column in function`, leaving out whatever is not known about `address`
+    pub fn describe(&self, address: usize) -> String {
+        let mut description = self.source_file.clone();
+
+        if let Some(position) = self.position(address) {
+            description = format!("{}:{}", description, position);
+        }
+        if let Some(function) = self.function(address) {
+            description = format!("{} in {}", description, function.name);
+        }
+
+        description
+    }
+}
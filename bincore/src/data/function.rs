@@ -1,6 +1,9 @@
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
 use crate::executable::runnable::Instruction;
 use crate::executable::runtime::Runtime;
 use serde::{Deserialize, Serialize};
+use std::rc::Rc;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Function {
@@ -9,4 +12,22 @@ pub struct Function {
     pub instructions: Vec<Instruction>, // pub last_is_variadic: bool,
 }
 
-pub type FunctionSignature = fn(&mut Runtime);
+/// Entry point of a `proseso` that a host can call by name
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FunctionExport {
+    pub address: usize,
+    /// heap addresses the arguments are stored in, in declaration order
+    pub args: Vec<usize>,
+}
+
+/// Receives the arguments of an `ExternCall` in call order, the returned value is pushed onto the stack
+pub type FunctionSignature =
+    Rc<dyn Fn(&mut Runtime, Vec<Value>) -> Result<Option<Value>, RuntimeError>>;
+
+#[derive(Clone)]
+pub struct NativeFunction {
+    /// number of fixed arguments, variadic functions accept any amount on top of these
+    pub arity: usize,
+    pub variadic: bool,
+    pub function: FunctionSignature,
+}
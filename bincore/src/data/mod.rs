@@ -1,3 +1,6 @@
+pub mod container;
+pub mod convert;
+pub mod debug_info;
 pub mod function;
 pub mod object;
 pub mod program_file;
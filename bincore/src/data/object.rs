@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::rc::Rc;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 pub struct ObjectDescriptor {
     pub name: String,
     pub members: HashMap<String, usize>,
@@ -15,3 +15,20 @@ pub struct Object {
     pub descriptor: Rc<ObjectDescriptor>,
     pub members: Vec<Value>,
 }
+
+impl ObjectDescriptor {
+    /// A descriptor whose members are indexed in the order of `members_by_index`
+    pub fn new(name: &str, members_by_index: Vec<String>) -> ObjectDescriptor {
+        let members = members_by_index
+            .iter()
+            .enumerate()
+            .map(|(index, member)| (member.clone(), index))
+            .collect();
+
+        ObjectDescriptor {
+            name: name.to_string(),
+            members,
+            members_by_index,
+        }
+    }
+}
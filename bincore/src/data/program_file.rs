@@ -1,16 +1,24 @@
+use crate::data::debug_info::DebugInfo;
+use crate::data::function::FunctionExport;
 use crate::data::object::ObjectDescriptor;
 use crate::data::value::Value;
 use crate::executable::runnable::Instruction;
 use crate::executable::runtime::Runtime;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct Program {
     pub instructions: Vec<Instruction>,
     pub strings: Vec<String>,
     pub heap_size: usize,
 
     pub object_descriptor: Vec<ObjectDescriptor>,
+    pub exports: HashMap<String, FunctionExport>,
+
+    /// names and source positions, left out of stripped programs
+    #[serde(default)]
+    pub debug: Option<DebugInfo>,
 }
 
 impl Program {
@@ -19,6 +27,8 @@ impl Program {
             instructions: self.instructions,
             strings: self.strings.clone(),
             object_descriptor: self.object_descriptor,
+            exports: self.exports,
+            debug: self.debug,
             heap: vec![Value::Int(0); self.heap_size],
 
             ..Runtime::new()
@@ -29,6 +39,7 @@ impl Program {
         }
 
         runtime.string_object_init_counter = self.strings.len();
+        runtime.string_constants = (0..self.strings.len()).collect();
 
         runtime
     }
@@ -9,15 +9,30 @@ pub enum Value {
     ListRef(usize),
     StrRef(usize),
     ObjectRef(usize),
+    Null,
 }
 
 impl Value {
-    pub fn is_object_ref(&self) -> bool {
+    pub fn type_name(&self) -> &'static str {
         match self {
-            Value::ObjectRef(_) => true,
-            _ => false,
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Char(_) => "char",
+            Value::Bool(_) => "bool",
+            Value::ListRef(_) => "list",
+            Value::StrRef(_) => "string",
+            Value::ObjectRef(_) => "object",
+            Value::Null => "null",
         }
     }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn is_object_ref(&self) -> bool {
+        matches!(self, Value::ObjectRef(_))
+    }
     pub fn as_int(&self) -> Option<i64> {
         match self {
             Value::Int(value) => Some(*value),
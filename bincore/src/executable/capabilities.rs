@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+
+/// What a program may access outside of the runtime, everything is denied by default
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    /// directories the file module may read and write under, canonicalized when allowed
+    file_roots: Vec<PathBuf>,
+    /// whether environment variables may be read
+    pub environment: bool,
+}
+
+impl Capabilities {
+    pub fn new() -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Allows files under `root`, which has to exist as it is resolved right away
+    pub fn allow_directory<P: AsRef<Path>>(mut self, root: P) -> std::io::Result<Capabilities> {
+        self.file_roots.push(root.as_ref().canonicalize()?);
+        Ok(self)
+    }
+
+    pub fn allow_environment(mut self) -> Capabilities {
+        self.environment = true;
+        self
+    }
+
+    pub fn file_roots(&self) -> &[PathBuf] {
+        &self.file_roots
+    }
+
+    /// Resolves `path` and returns it if it lies inside one of the allowed roots
+    ///
+    /// The path does not need to exist yet, but its parent directory does. A symbolic link is
+    /// checked by where it points, so one that points nowhere is rejected.
+    pub fn check_path(&self, path: &str) -> Option<PathBuf> {
+        let path = Path::new(path);
+        let resolved = match path.canonicalize() {
+            Ok(resolved) => resolved,
+            // it exists but cannot be resolved, e.g. a link to a file that does not exist
+            Err(_) if path.symlink_metadata().is_ok() => return None,
+            Err(_) => {
+                let parent = match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+                parent.canonicalize().ok()?.join(path.file_name()?)
+            }
+        };
+
+        self.file_roots
+            .iter()
+            .any(|root| resolved.starts_with(root))
+            .then_some(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// An empty directory of its own for every test, with `root` and `outside` in it
+    fn directory(test: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "bincore-capabilities-{}-{}",
+            std::process::id(),
+            test
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("root")).unwrap();
+        fs::create_dir_all(directory.join("outside")).unwrap();
+        fs::write(directory.join("outside").join("secret.txt"), "secret").unwrap();
+
+        directory.canonicalize().unwrap()
+    }
+
+    fn check(capabilities: &Capabilities, path: PathBuf) -> Option<PathBuf> {
+        capabilities.check_path(path.to_str().unwrap())
+    }
+
+    #[test]
+    fn allows_paths_inside_a_root() {
+        let directory = directory("inside");
+        let root = directory.join("root");
+        fs::write(root.join("data.txt"), "data").unwrap();
+        let capabilities = Capabilities::new().allow_directory(&root).unwrap();
+
+        assert_eq!(
+            check(&capabilities, root.join("data.txt")),
+            Some(root.join("data.txt"))
+        );
+        assert_eq!(
+            check(&capabilities, root.join("new.txt")),
+            Some(root.join("new.txt"))
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn rejects_paths_outside_of_every_root() {
+        let directory = directory("outside");
+        let root = directory.join("root");
+        let capabilities = Capabilities::new().allow_directory(&root).unwrap();
+
+        assert_eq!(Capabilities::new().check_path(root.to_str().unwrap()), None);
+        assert_eq!(
+            check(&capabilities, directory.join("outside/secret.txt")),
+            None
+        );
+        assert_eq!(
+            check(&capabilities, root.join("../outside/secret.txt")),
+            None
+        );
+        assert_eq!(check(&capabilities, root.join("../new.txt")), None);
+        assert_eq!(check(&capabilities, root.join("missing/new.txt")), None);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn roots_have_to_exist() {
+        let directory = directory("missing");
+
+        assert!(Capabilities::new()
+            .allow_directory(directory.join("missing"))
+            .is_err());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_are_checked_by_their_target() {
+        use std::os::unix::fs::symlink;
+
+        let directory = directory("links");
+        let root = directory.join("root");
+        let capabilities = Capabilities::new().allow_directory(&root).unwrap();
+
+        fs::write(root.join("data.txt"), "data").unwrap();
+        symlink(root.join("data.txt"), root.join("inside")).unwrap();
+        symlink(directory.join("outside/secret.txt"), root.join("existing")).unwrap();
+        symlink(directory.join("outside/new.txt"), root.join("dangling")).unwrap();
+
+        assert_eq!(
+            check(&capabilities, root.join("inside")),
+            Some(root.join("data.txt"))
+        );
+        assert_eq!(check(&capabilities, root.join("existing")), None);
+        assert_eq!(check(&capabilities, root.join("dangling")), None);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
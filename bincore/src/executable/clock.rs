@@ -0,0 +1,98 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Source of time for the time builtins, replaced with a `FakeClock` to make runs deterministic
+pub trait Clock {
+    /// Wall clock time since the unix epoch
+    fn now(&self) -> Duration;
+    /// Monotonic time since the clock was created
+    fn elapsed(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+/// The operating system's clocks
+#[derive(Debug, Clone)]
+pub struct SystemClock {
+    started: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// A clock that only moves when advanced or slept on, clones share the same time
+///
+/// # Example
+/// ```
+/// use bincore::executable::clock::{Clock, FakeClock};
+/// use std::time::Duration;
+///
+/// let clock = FakeClock::new(Duration::from_secs(1_000));
+/// let handle = clock.clone();
+///
+/// clock.sleep(Duration::from_millis(250));
+/// handle.advance(Duration::from_millis(750));
+///
+/// assert_eq!(clock.now(), Duration::from_secs(1_001));
+/// assert_eq!(clock.elapsed(), Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FakeClock {
+    start: Duration,
+    elapsed: Rc<Cell<Duration>>,
+}
+
+impl FakeClock {
+    /// A clock that reads `start` since the unix epoch
+    pub fn new(start: Duration) -> FakeClock {
+        FakeClock {
+            start,
+            elapsed: Rc::new(Cell::new(Duration::ZERO)),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.set(self.elapsed.get() + duration);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.start + self.elapsed.get()
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.elapsed.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::data::value::Value;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeError {
+    FunctionNotFound(String),
+    ExternMismatch {
+        function: String,
+        arity: usize,
+        variadic: bool,
+    },
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    TypeMismatch {
+        expected: &'static str,
+        found: Value,
+    },
+    InvalidReference(Value),
+    IndexOutOfBounds {
+        index: i64,
+        length: usize,
+    },
+    InstructionOutOfBounds(usize),
+    DivisionByZero,
+    IntegerOverflow,
+    /// a `ret` ran with no call to return to
+    ReturnOutsideFunction,
+    /// an instruction popped more values than the stack held
+    StackUnderflow,
+    Io(String),
+    PermissionDenied(String),
+    Native(String),
+    /// Raised by `process.exit`, ends the program with the given status rather than a failure
+    Exit(i32),
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::FunctionNotFound(name) => write!(f, "Function '{}' not found", name),
+            RuntimeError::ExternMismatch {
+                function,
+                arity,
+                variadic,
+            } => write!(
+                f,
+                "Extern '{}' is registered with a different signature than the {}{} argument(s) in its manifest",
+                function,
+                arity,
+                if *variadic { " or more" } else { "" }
+            ),
+            RuntimeError::ArgumentCount {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Function '{}' expects {} argument(s), got {}",
+                function, expected, found
+            ),
+            RuntimeError::TypeMismatch { expected, found } => write!(
+                f,
+                "Expected a value of type {}, got {}",
+                expected,
+                found.type_name()
+            ),
+            RuntimeError::InvalidReference(value) => {
+                write!(f, "Invalid {} reference {:?}", value.type_name(), value)
+            }
+            RuntimeError::IndexOutOfBounds { index, length } => write!(
+                f,
+                "Index {} is out of bounds for length {}",
+                index, length
+            ),
+            RuntimeError::InstructionOutOfBounds(address) => {
+                write!(f, "Instruction pointer {} is out of bounds", address)
+            }
+            RuntimeError::DivisionByZero => write!(f, "Division by zero"),
+            RuntimeError::IntegerOverflow => write!(f, "Integer overflow"),
+            RuntimeError::ReturnOutsideFunction => write!(f, "Return outside of a function"),
+            RuntimeError::StackUnderflow => write!(f, "Stack underflow"),
+            RuntimeError::Io(message) => write!(f, "IO error: {}", message),
+            RuntimeError::PermissionDenied(action) => write!(f, "Permission denied: {}", action),
+            RuntimeError::Native(message) => write!(f, "{}", message),
+            RuntimeError::Exit(code) => write!(f, "Program exited with code {}", code),
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+impl From<std::io::Error> for RuntimeError {
+    fn from(error: std::io::Error) -> Self {
+        RuntimeError::Io(error.to_string())
+    }
+}
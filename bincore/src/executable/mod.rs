@@ -1,2 +1,10 @@
+pub mod capabilities;
+pub mod clock;
+pub mod error;
+pub mod native;
+pub mod output;
+pub mod profiler;
+pub mod random;
 pub mod runnable;
 pub mod runtime;
+pub mod verifier;
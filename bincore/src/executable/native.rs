@@ -0,0 +1,163 @@
+use crate::data::convert::{FromValue, IntoValue};
+use crate::data::function::NativeFunction;
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
+use crate::executable::runtime::Runtime;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::vec::IntoIter;
+
+/// Collects every remaining argument, only valid as the last parameter of a native function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variadic<T>(pub Vec<T>);
+
+/// A parameter of a native function, taken from the arguments of an `ExternCall`
+pub trait NativeParameter: Sized {
+    const VARIADIC: bool = false;
+
+    fn take(args: &mut IntoIter<Value>, runtime: &Runtime) -> Result<Self, RuntimeError>;
+}
+
+impl<T: FromValue> NativeParameter for T {
+    fn take(args: &mut IntoIter<Value>, runtime: &Runtime) -> Result<Self, RuntimeError> {
+        match args.next() {
+            Some(value) => T::from_value(value, runtime),
+            None => Err(RuntimeError::Native("Missing argument".to_string())),
+        }
+    }
+}
+
+impl<T: FromValue> NativeParameter for Variadic<T> {
+    const VARIADIC: bool = true;
+
+    fn take(args: &mut IntoIter<Value>, runtime: &Runtime) -> Result<Self, RuntimeError> {
+        args.map(|value| T::from_value(value, runtime))
+            .collect::<Result<Vec<T>, RuntimeError>>()
+            .map(Variadic)
+    }
+}
+
+/// The result of a native function, `()` pushes nothing onto the stack
+pub trait NativeReturn {
+    fn into_return(self, runtime: &mut Runtime) -> Result<Option<Value>, RuntimeError>;
+}
+
+impl NativeReturn for () {
+    fn into_return(self, _: &mut Runtime) -> Result<Option<Value>, RuntimeError> {
+        Ok(None)
+    }
+}
+
+impl<T: IntoValue> NativeReturn for T {
+    fn into_return(self, runtime: &mut Runtime) -> Result<Option<Value>, RuntimeError> {
+        Ok(Some(self.into_value(runtime)))
+    }
+}
+
+impl<T: NativeReturn> NativeReturn for Result<T, RuntimeError> {
+    fn into_return(self, runtime: &mut Runtime) -> Result<Option<Value>, RuntimeError> {
+        self?.into_return(runtime)
+    }
+}
+
+/// Implemented for functions and closures taking `&mut Runtime` followed by typed parameters
+///
+/// # Example
+/// ```
+/// use bincore::executable::native::Variadic;
+/// use bincore::executable::runtime::Runtime;
+///
+/// let mut runtime = Runtime::new();
+/// runtime.register_native("doble", |_: &mut Runtime, value: i64| value * 2);
+/// runtime.register_native("sumada", |_: &mut Runtime, values: Variadic<i64>| {
+///     values.0.iter().sum::<i64>()
+/// });
+///
+/// assert_eq!(runtime.functions["doble"].arity, 1);
+/// assert!(runtime.functions["sumada"].variadic);
+/// ```
+pub trait IntoNativeFunction<Args> {
+    fn into_native_function(self) -> NativeFunction;
+}
+
+macro_rules! impl_into_native_function {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg,)*> IntoNativeFunction<($($arg,)*)> for F
+        where
+            F: Fn(&mut Runtime, $($arg),*) -> R + 'static,
+            R: NativeReturn,
+            $($arg: NativeParameter,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_native_function(self) -> NativeFunction {
+                let parameters: &[bool] = &[$($arg::VARIADIC),*];
+                let variadic = parameters.last().copied().unwrap_or(false);
+
+                NativeFunction {
+                    arity: parameters.len() - variadic as usize,
+                    variadic,
+                    function: Rc::new(move |runtime: &mut Runtime, args: Vec<Value>| {
+                        let mut args = args.into_iter();
+                        $(let $arg = $arg::take(&mut args, runtime)?;)*
+
+                        self(runtime, $($arg),*).into_return(runtime)
+                    }),
+                }
+            }
+        }
+    };
+}
+
+impl_into_native_function!();
+impl_into_native_function!(A);
+impl_into_native_function!(A, B);
+impl_into_native_function!(A, B, C);
+impl_into_native_function!(A, B, C, D);
+impl_into_native_function!(A, B, C, D, E);
+impl_into_native_function!(A, B, C, D, E, G);
+
+/// A named group of natives, registered on a `Runtime` as `<module>.<function>`
+///
+/// Functions of a module with an empty name are registered without a prefix.
+#[derive(Clone, Default)]
+pub struct NativeModule {
+    pub name: String,
+    pub functions: HashMap<String, NativeFunction>,
+    /// registered without a prefix, for names programs used before there were modules
+    pub builtins: HashMap<String, NativeFunction>,
+}
+
+impl NativeModule {
+    pub fn new(name: &str) -> NativeModule {
+        NativeModule {
+            name: name.to_string(),
+            functions: HashMap::new(),
+            builtins: HashMap::new(),
+        }
+    }
+
+    pub fn function<Args, F: IntoNativeFunction<Args>>(mut self, name: &str, function: F) -> Self {
+        self.functions
+            .insert(name.to_string(), function.into_native_function());
+        self
+    }
+
+    /// Also exposes the function `name` of this module without a prefix
+    pub fn builtin(mut self, name: &str) -> Self {
+        let function = self.functions[name].clone();
+        self.builtins.insert(name.to_string(), function);
+        self
+    }
+
+    pub fn qualified_name(&self, function: &str) -> String {
+        qualified_name(&self.name, function)
+    }
+}
+
+pub fn qualified_name(module: &str, function: &str) -> String {
+    if module.is_empty() {
+        function.to_string()
+    } else {
+        format!("{}.{}", module, function)
+    }
+}
@@ -0,0 +1,53 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+/// An output sink that can still be read after it was handed to a `Runtime`
+///
+/// # Example
+/// ```
+/// use bincore::data::value::Value;
+/// use bincore::executable::output::SharedBuffer;
+/// use bincore::executable::runtime::Runtime;
+/// use bincore::stdlib;
+///
+/// let output = SharedBuffer::new();
+///
+/// let mut runtime = Runtime::new();
+/// runtime.register_module(stdlib::builtins());
+/// runtime.set_stdout(output.clone());
+///
+/// let function = runtime.functions["ipakita"].function.clone();
+/// function(&mut runtime, vec![Value::Int(1), Value::Bool(true)]).unwrap();
+///
+/// assert_eq!(output.contents(), "1 true\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SharedBuffer {
+    buffer: Rc<RefCell<Vec<u8>>>,
+}
+
+impl SharedBuffer {
+    pub fn new() -> SharedBuffer {
+        SharedBuffer::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).to_string()
+    }
+
+    pub fn clear(&self) {
+        self.buffer.borrow_mut().clear();
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+use crate::executable::runnable::Instruction;
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Address used for the implicit frame that wraps top level code
+pub const ENTRY_ADDRESS: usize = 0;
+
+#[derive(Debug, Clone, Default)]
+pub struct FunctionProfile {
+    pub calls: u64,
+    pub instructions: u64,
+    pub inclusive: Duration,
+    pub exclusive: Duration,
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    address: usize,
+    started: Instant,
+}
+
+/// Collects per instruction and per function statistics while a `Runtime` runs
+///
+/// Functions are identified by the address they are called at, `Call` pushes a frame and `Ret`
+/// pops it. Top level code runs inside an entry frame at `ENTRY_ADDRESS`.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    pub instruction_counts: Vec<u64>,
+    pub functions: HashMap<usize, FunctionProfile>,
+    pub function_names: HashMap<usize, String>,
+    /// exclusive time spent in each unique call stack, keyed by the addresses from the entry frame down
+    pub stacks: HashMap<Vec<usize>, Duration>,
+
+    frames: Vec<Frame>,
+    last_transition: Option<Instant>,
+    total: Duration,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    /// Names used in reports instead of the raw function address
+    pub fn with_function_names(mut self, names: HashMap<usize, String>) -> Profiler {
+        self.function_names = names;
+        self
+    }
+
+    pub fn function_name(&self, address: usize) -> String {
+        match self.function_names.get(&address) {
+            Some(name) => name.clone(),
+            None if address == ENTRY_ADDRESS => "_start".to_string(),
+            None => format!("fn@{}", address),
+        }
+    }
+
+    pub fn start(&mut self, instructions_length: usize) {
+        if self.instruction_counts.len() < instructions_length {
+            self.instruction_counts.resize(instructions_length, 0);
+        }
+
+        let now = Instant::now();
+        self.last_transition = Some(now);
+        self.frames.push(Frame {
+            address: ENTRY_ADDRESS,
+            started: now,
+        });
+        self.functions.entry(ENTRY_ADDRESS).or_default().calls += 1;
+    }
+
+    #[inline]
+    pub fn record(&mut self, address: usize, instruction: &Instruction) {
+        self.instruction_counts[address] += 1;
+
+        if let Some(frame) = self.frames.last() {
+            self.functions
+                .entry(frame.address)
+                .or_default()
+                .instructions += 1;
+        }
+
+        match instruction {
+            Instruction::Call { address } => self.enter(*address),
+            Instruction::Ret => self.exit(),
+            _ => {}
+        }
+    }
+
+    pub fn finish(&mut self) {
+        while !self.frames.is_empty() {
+            self.exit();
+        }
+        self.last_transition = None;
+    }
+
+    fn enter(&mut self, address: usize) {
+        let now = self.transition();
+
+        self.functions.entry(address).or_default().calls += 1;
+        self.frames.push(Frame {
+            address,
+            started: now,
+        });
+    }
+
+    fn exit(&mut self) {
+        let now = self.transition();
+
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        // recursive calls are already covered by the outermost frame of the same function
+        if self
+            .frames
+            .iter()
+            .all(|outer| outer.address != frame.address)
+        {
+            let elapsed = now - frame.started;
+            self.functions.entry(frame.address).or_default().inclusive += elapsed;
+
+            if frame.address == ENTRY_ADDRESS {
+                self.total += elapsed;
+            }
+        }
+    }
+
+    /// Attributes the time since the last call or return to the frame on top of the stack
+    fn transition(&mut self) -> Instant {
+        let now = Instant::now();
+        let elapsed = match self.last_transition {
+            Some(last) => now - last,
+            None => Duration::ZERO,
+        };
+        self.last_transition = Some(now);
+
+        if let Some(frame) = self.frames.last() {
+            self.functions.entry(frame.address).or_default().exclusive += elapsed;
+
+            let stack = self.frames.iter().map(|frame| frame.address).collect();
+            *self.stacks.entry(stack).or_default() += elapsed;
+        }
+
+        now
+    }
+
+    pub fn write_report<W: Write>(
+        &self,
+        instructions: &[Instruction],
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        writeln!(out, "Total time: {:.3}ms", millis(self.total))?;
+        writeln!(out)?;
+
+        let mut functions = self.functions.iter().collect::<Vec<_>>();
+        functions.sort_by_key(|(_, profile)| std::cmp::Reverse(profile.inclusive));
+
+        writeln!(
+            out,
+            "{:<24} {:>10} {:>14} {:>14} {:>14}",
+            "function", "calls", "instructions", "inclusive ms", "exclusive ms"
+        )?;
+        for (address, profile) in functions {
+            writeln!(
+                out,
+                "{:<24} {:>10} {:>14} {:>14.3} {:>14.3}",
+                self.function_name(*address),
+                profile.calls,
+                profile.instructions,
+                millis(profile.inclusive),
+                millis(profile.exclusive)
+            )?;
+        }
+        writeln!(out)?;
+
+        let mut hot = self
+            .instruction_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .collect::<Vec<_>>();
+        hot.sort_by(|(left_address, left), (right_address, right)| {
+            right.cmp(left).then(left_address.cmp(right_address))
+        });
+
+        writeln!(out, "{:>8} {:>12}  instruction", "address", "count")?;
+        for (address, count) in hot {
+            let instruction = match instructions.get(address) {
+                Some(instruction) => format!("{:?}", instruction),
+                None => "?".to_string(),
+            };
+            writeln!(out, "{:>8} {:>12}  {}", address, count, instruction)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes one line per call stack in the folded format read by `flamegraph.pl` and `inferno`,
+    /// weights are in nanoseconds
+    pub fn write_folded<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        let mut stacks = self
+            .stacks
+            .iter()
+            .map(|(stack, elapsed)| {
+                let names = stack
+                    .iter()
+                    .map(|address| self.function_name(*address))
+                    .collect::<Vec<String>>();
+                (names.join(";"), elapsed.as_nanos())
+            })
+            .filter(|(_, nanos)| *nanos > 0)
+            .collect::<Vec<_>>();
+        stacks.sort();
+
+        for (stack, nanos) in stacks {
+            writeln!(out, "{} {}", stack, nanos)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly::assembler::assemble;
+    use crate::executable::runtime::Runtime;
+
+    fn profile(code: &str) -> Profiler {
+        let mut runtime: Runtime = assemble(code).unwrap().into_runtime();
+        runtime.enable_profiling();
+        runtime.run().unwrap();
+        runtime.profiler.unwrap()
+    }
+
+    #[test]
+    fn counts_calls_and_instructions() {
+        let profiler = profile(
+            "
+            .code
+                nop
+                call twice
+                call twice
+                jump end
+            twice:
+                nop
+                nop
+                ret
+            end:
+                nop
+            ",
+        );
+
+        assert_eq!(profiler.functions[&ENTRY_ADDRESS].calls, 1);
+        assert_eq!(profiler.functions[&4].calls, 2);
+        assert_eq!(profiler.functions[&4].instructions, 6);
+        // programs start after the `nop` at address 0
+        assert_eq!(profiler.functions[&ENTRY_ADDRESS].instructions, 4);
+        assert_eq!(profiler.instruction_counts, vec![0, 1, 1, 1, 2, 2, 2, 1]);
+        assert_eq!(profiler.function_name(4), "fn@4");
+        assert_eq!(profiler.function_name(ENTRY_ADDRESS), "_start");
+    }
+
+    #[test]
+    fn counts_every_recursive_call_and_its_time_once() {
+        let profiler = profile(
+            "
+            .heap 1
+            .code
+                nop
+                push int 3
+                store 0
+                call countdown
+                jump end
+            countdown:
+                load 0
+                push int 0
+                gt
+                jump_if_false done
+                load 0
+                push int 1
+                sub
+                store 0
+                call countdown
+            done:
+                ret
+            end:
+                nop
+            ",
+        );
+
+        let countdown = &profiler.functions[&5];
+        assert_eq!(countdown.calls, 4);
+        assert_eq!(profiler.instruction_counts[5], 4);
+        assert_eq!(profiler.instruction_counts[14], 4);
+        // the nested frames of `countdown` run inside the outermost one
+        assert!(countdown.inclusive <= profiler.functions[&ENTRY_ADDRESS].inclusive);
+        assert!(profiler.frames.is_empty());
+
+        let deepest = vec![ENTRY_ADDRESS, 5, 5, 5, 5];
+        assert!(profiler.stacks.contains_key(&deepest));
+        assert!(profiler.stacks.keys().all(|stack| stack.len() <= 5));
+    }
+
+    #[test]
+    fn writes_folded_stacks_by_name() {
+        let mut profiler =
+            Profiler::new().with_function_names(HashMap::from([(4, "square".to_string())]));
+        profiler
+            .stacks
+            .insert(vec![ENTRY_ADDRESS], Duration::from_nanos(250));
+        profiler
+            .stacks
+            .insert(vec![ENTRY_ADDRESS, 4], Duration::from_nanos(1_000));
+        profiler
+            .stacks
+            .insert(vec![ENTRY_ADDRESS, 4, 9], Duration::from_nanos(30));
+        // stacks that took no measurable time are left out
+        profiler
+            .stacks
+            .insert(vec![ENTRY_ADDRESS, 9], Duration::ZERO);
+
+        let mut folded = vec![];
+        profiler.write_folded(&mut folded).unwrap();
+
+        assert_eq!(
+            String::from_utf8(folded).unwrap(),
+            "_start 250\n_start;square 1000\n_start;square;fn@9 30\n"
+        );
+    }
+}
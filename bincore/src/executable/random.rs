@@ -0,0 +1,83 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A small SplitMix64 generator, the same seed always produces the same sequence
+///
+/// # Example
+/// ```
+/// use bincore::executable::random::Random;
+///
+/// let mut first = Random::new(7);
+/// let mut second = Random::new(7);
+///
+/// assert_eq!(first.next_u64(), second.next_u64());
+/// assert!((1..=6).contains(&first.int_in_range(1, 6)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    /// A generator seeded from the current time
+    pub fn from_time() -> Random {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Random::new(nanos as u64 ^ (nanos >> 64) as u64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    /// A float in `[0, 1)`
+    pub fn float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// An integer in `[low, high]`, `low` must not be greater than `high`
+    pub fn int_in_range(&mut self, low: i64, high: i64) -> i64 {
+        let span = (high.wrapping_sub(low) as u64).wrapping_add(1);
+        if span == 0 {
+            return self.next_u64() as i64;
+        }
+
+        // rejects the top of the range that would make some values more likely than others
+        let zone = u64::MAX - (u64::MAX - span + 1) % span;
+        loop {
+            let value = self.next_u64();
+            if value <= zone {
+                return low.wrapping_add((value % span) as i64);
+            }
+        }
+    }
+
+    /// An index in `[0, length)`, `length` must not be zero
+    pub fn index(&mut self, length: usize) -> usize {
+        self.int_in_range(0, length as i64 - 1) as usize
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for last in (1..items.len()).rev() {
+            let other = self.index(last + 1);
+            items.swap(last, other);
+        }
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Random::from_time()
+    }
+}
@@ -1,8 +1,19 @@
-use crate::data::function::FunctionSignature;
+use crate::data::convert::{FromValue, IntoValue};
+use crate::data::debug_info::DebugInfo;
+use crate::data::function::{FunctionExport, NativeFunction};
 use crate::data::object::{Object, ObjectDescriptor};
+use crate::data::program_file::Program;
 use crate::data::value::Value;
+use crate::executable::capabilities::Capabilities;
+use crate::executable::clock::{Clock, SystemClock};
+use crate::executable::error::RuntimeError;
+use crate::executable::native::{IntoNativeFunction, NativeModule};
+use crate::executable::profiler::Profiler;
+use crate::executable::random::Random;
 use crate::executable::runnable::Instruction;
+use crate::stdlib::NativeRegistry;
 use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
 use std::rc::Rc;
 
 
@@ -10,16 +21,19 @@ const STACK_SIZE: usize = 1024;
 const STACK_THRESHOLD: usize = 10;
 
 macro_rules! bin_op_2 {
-    ($left:expr, $right:expr, $op:tt) => {
+    ($left:expr, $right:expr, $checked:ident, $op:tt) => {
         match ($left, $right) {
-            (Value::Int(left), Value::Int(right)) => {
-                Value::Int(left $op right)
-            }
+            (Value::Int(left), Value::Int(right)) => match left.$checked(right) {
+                Some(value) => Value::Int(value),
+                // only division and remainder fail on a zero right operand
+                None if right == 0 => return Err(RuntimeError::DivisionByZero),
+                None => return Err(RuntimeError::IntegerOverflow),
+            },
             (Value::Float(left), Value::Float(right)) => {
                 Value::Float(left $op right)
             }
-            _ => {
-                panic!("Expected two values of the same type")
+            (left, right) => {
+                return Err(operand_mismatch("int or float", left, right))
             }
         }
     }
@@ -34,13 +48,32 @@ macro_rules! bin_op_2_comp {
             (Value::Float(left), Value::Float(right)) => {
                 Value::Bool(left $op right)
             }
-            _ => {
-                panic!("Expected two values of the same type")
+            (left, right) => {
+                return Err(operand_mismatch("int or float", left, right))
             }
         }
     }
 }
 
+/// The error for an operation that takes two operands of one of the `expected` types, written
+/// like `"int or float"`: the left operand is wrong unless it has one of them
+fn operand_mismatch(expected: &'static str, left: Value, right: Value) -> RuntimeError {
+    if expected
+        .split(" or ")
+        .any(|type_name| type_name == left.type_name())
+    {
+        RuntimeError::TypeMismatch {
+            expected: left.type_name(),
+            found: right,
+        }
+    } else {
+        RuntimeError::TypeMismatch {
+            expected,
+            found: left,
+        }
+    }
+}
+
 pub struct Runtime {
     pub instructions: Vec<Instruction>,
     pub instruction_pointer: usize,
@@ -48,7 +81,8 @@ pub struct Runtime {
 
     pub stack: Vec<Value>,
     pub stack_pointer: usize,
-    pub functions: HashMap<String, FunctionSignature>,
+    pub functions: HashMap<String, NativeFunction>,
+    pub exports: HashMap<String, FunctionExport>,
     pub call_stack: Vec<usize>,
     pub heap: Vec<Value>,
 
@@ -60,6 +94,27 @@ pub struct Runtime {
     pub lists: HashMap<usize, Vec<Value>>,
     pub string_object_init_counter: usize,
     pub string_objects: HashMap<usize, String>,
+    /// id in `string_objects` of each of `strings`, the constants `Push` refers to by that id
+    pub string_constants: Vec<usize>,
+
+    pub profiler: Option<Profiler>,
+    pub debug: Option<DebugInfo>,
+
+    /// arguments passed to the program itself, read by `process.args`
+    pub args: Vec<String>,
+    pub stdin: Box<dyn BufRead>,
+    pub stdout: Box<dyn Write>,
+    pub stderr: Box<dyn Write>,
+
+    pub capabilities: Capabilities,
+    pub clock: Box<dyn Clock>,
+    pub random: Random,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Runtime {
@@ -72,6 +127,7 @@ impl Runtime {
             stack: vec![Value::Int(0); STACK_SIZE],
             stack_pointer: 0,
             functions: HashMap::new(),
+            exports: HashMap::new(),
             call_stack: vec![],
             heap: vec![],
 
@@ -83,14 +139,127 @@ impl Runtime {
             lists: Default::default(),
             string_object_init_counter: 0,
             string_objects: Default::default(),
+            string_constants: vec![],
+
+            profiler: None,
+            debug: None,
+
+            args: vec![],
+            stdin: Box::new(BufReader::new(std::io::stdin())),
+            stdout: Box::new(std::io::stdout()),
+            stderr: Box::new(std::io::stderr()),
+
+            capabilities: Capabilities::new(),
+            clock: Box::new(SystemClock::new()),
+            random: Random::from_time(),
         }
     }
 
     #[inline]
-    pub fn register_function(&mut self, name: String, function: FunctionSignature) {
+    pub fn register_function(&mut self, name: String, function: NativeFunction) {
         self.functions.insert(name, function);
     }
 
+    /// Registers a Rust function as an extern, see `IntoNativeFunction`
+    pub fn register_native<Args, F: IntoNativeFunction<Args>>(&mut self, name: &str, function: F) {
+        self.register_function(name.to_string(), function.into_native_function());
+    }
+
+    /// Replaces the stream the input builtins read from
+    pub fn set_stdin<R: BufRead + 'static>(&mut self, stdin: R) {
+        self.stdin = Box::new(stdin);
+    }
+
+    /// Replaces the stream prompts and program output are written to
+    pub fn set_stdout<W: Write + 'static>(&mut self, stdout: W) {
+        self.stdout = Box::new(stdout);
+    }
+
+    /// Replaces the stream diagnostics are written to
+    pub fn set_stderr<W: Write + 'static>(&mut self, stderr: W) {
+        self.stderr = Box::new(stderr);
+    }
+
+    /// Replaces the clock the time builtins read, see `FakeClock`
+    pub fn set_clock<C: Clock + 'static>(&mut self, clock: C) {
+        self.clock = Box::new(clock);
+    }
+
+    /// Exposes every function of `module` to the program
+    pub fn register_module(&mut self, module: NativeModule) {
+        for (name, function) in module.functions.iter() {
+            self.register_function(module.qualified_name(name), function.clone());
+        }
+        for (name, function) in module.builtins {
+            self.register_function(name, function);
+        }
+    }
+
+    /// Checks that every extern the program calls is registered, and that registered externs
+    /// agree with the registry on their arity
+    pub fn link(&self, registry: &NativeRegistry) -> Result<(), RuntimeError> {
+        for (name, function) in self.functions.iter() {
+            if let Some(descriptor) = registry.get(name) {
+                if !descriptor.matches(function) {
+                    return Err(RuntimeError::ExternMismatch {
+                        function: name.clone(),
+                        arity: descriptor.arity,
+                        variadic: descriptor.variadic,
+                    });
+                }
+            }
+        }
+
+        for instruction in self.instructions.iter() {
+            if let Instruction::ExternCall { string_id } = instruction {
+                let name = &self.strings[*string_id];
+                if !self.functions.contains_key(name) {
+                    return Err(RuntimeError::FunctionNotFound(name.clone()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Takes over the code of `program`, a later version of the running one whose existing
+    /// instructions stayed where they were, as `BinLangTranslationUnit::append` makes them
+    ///
+    /// The heap, the stacks and every list, object and string made so far are kept, the heap
+    /// grows if the new code uses more variables. String constants that are new or changed get
+    /// fresh ids, never one a string made while running holds, and the `Push`es of them are
+    /// rewritten to match. Where execution continues is left to the caller, and so is linking:
+    /// externs the new code calls are not checked until `link` runs again.
+    pub fn reload(&mut self, program: &Program) {
+        let mut string_constants = Vec::with_capacity(program.strings.len());
+        for (index, string) in program.strings.iter().enumerate() {
+            let id = match self.string_constants.get(index) {
+                Some(&id) if self.strings.get(index) == Some(string) => id,
+                _ => self.insert_string(string.clone()),
+            };
+            string_constants.push(id);
+        }
+
+        self.instructions = program.instructions.clone();
+        for instruction in self.instructions.iter_mut() {
+            if let Instruction::Push {
+                value: Value::StrRef(string_id),
+            } = instruction
+            {
+                *string_id = string_constants[*string_id];
+            }
+        }
+        self.strings = program.strings.clone();
+        self.string_constants = string_constants;
+        self.object_descriptor = program.object_descriptor.clone();
+        self.exports = program.exports.clone();
+        self.debug = program.debug.clone();
+
+        if self.heap.len() < program.heap_size {
+            self.heap.resize(program.heap_size, Value::Int(0));
+        }
+    }
+
     #[inline]
     pub fn load_from_heap(&mut self, address: usize) -> Value {
         self.heap[address]
@@ -98,19 +267,128 @@ impl Runtime {
 
     #[inline]
     pub fn new_string(&mut self, string: String) -> Value {
+        Value::StrRef(self.insert_string(string))
+    }
+
+    #[inline]
+    fn insert_string(&mut self, string: String) -> usize {
         let string_id = self.string_object_init_counter;
         self.string_object_init_counter += 1;
 
-        self.string_objects.insert(string_id, string.clone());
+        self.string_objects.insert(string_id, string);
+
+        string_id
+    }
+
+    #[inline]
+    pub fn new_list(&mut self, values: Vec<Value>) -> Value {
+        let list_id = self.list_init_counter;
+        self.list_init_counter += 1;
+
+        self.lists.insert(list_id, values);
+
+        Value::ListRef(list_id)
+    }
+
+    /// Allocates an object outside of `CreateObject`, `members` are in the descriptor's index order
+    pub fn new_object(&mut self, descriptor: ObjectDescriptor, members: Vec<Value>) -> Value {
+        let object_id = self.object_init_counter;
+        self.object_init_counter += 1;
+
+        let object = Object {
+            descriptor: Rc::new(descriptor),
+            members,
+        };
+        self.objects.insert(object_id, object);
+
+        Value::ObjectRef(object_id)
+    }
+
+    /// The string `id` refers to, an error if no string has that id
+    fn string_object(&self, id: usize) -> Result<&String, RuntimeError> {
+        self.string_objects
+            .get(&id)
+            .ok_or(RuntimeError::InvalidReference(Value::StrRef(id)))
+    }
 
-        Value::StrRef(string_id)
+    /// The list `id` refers to, an error if no list has that id
+    fn list_object(&self, id: usize) -> Result<&Vec<Value>, RuntimeError> {
+        self.lists
+            .get(&id)
+            .ok_or(RuntimeError::InvalidReference(Value::ListRef(id)))
+    }
+
+    fn member(&mut self, object: Value, index: usize) -> Result<&mut Value, RuntimeError> {
+        let id = match object {
+            Value::ObjectRef(id) => id,
+            found => {
+                return Err(RuntimeError::TypeMismatch {
+                    expected: "object",
+                    found,
+                })
+            }
+        };
+
+        let members = match self.objects.get_mut(&id) {
+            Some(object) => &mut object.members,
+            None => return Err(RuntimeError::InvalidReference(object)),
+        };
+        let length = members.len();
+        members
+            .get_mut(index)
+            .ok_or(RuntimeError::IndexOutOfBounds {
+                index: index as i64,
+                length,
+            })
+    }
+
+    /// What `==` evaluates to, strings compare by contents and a char equals the string of it
+    pub fn values_equal(&self, left: Value, right: Value) -> Result<bool, RuntimeError> {
+        let equal = match (left, right) {
+            (Value::Char(left), Value::Char(right)) => left == right,
+            (Value::Int(left), Value::Int(right)) => left == right,
+            (Value::Float(left), Value::Float(right)) => left == right,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::StrRef(left), Value::StrRef(right)) => {
+                self.string_object(left)? == self.string_object(right)?
+            }
+            (Value::Null, other) | (other, Value::Null) => other.is_null(),
+            (Value::StrRef(left), Value::Char(right)) => {
+                *self.string_object(left)? == right.to_string()
+            }
+            (Value::Char(left), Value::StrRef(right)) => {
+                left.to_string() == *self.string_object(right)?
+            }
+            (Value::ListRef(left), Value::ListRef(right)) => {
+                self.list_object(left)? == self.list_object(right)?
+            }
+            (left, right) => {
+                return Err(operand_mismatch(
+                    "int or float or char or bool or string or list or null",
+                    left,
+                    right,
+                ))
+            }
+        };
+
+        Ok(equal)
+    }
+
+    pub fn to_value<T: IntoValue>(&mut self, value: T) -> Value {
+        value.into_value(self)
+    }
+
+    pub fn from_value<T: FromValue>(&self, value: Value) -> Result<T, RuntimeError> {
+        T::from_value(value, self)
     }
 
     #[inline]
-    pub fn stack_pop(&mut self) -> Value {
+    pub fn stack_pop(&mut self) -> Result<Value, RuntimeError> {
+        if self.stack_pointer == 0 {
+            return Err(RuntimeError::StackUnderflow);
+        }
         self.stack_pointer -= 1;
-        let value = self.stack[self.stack_pointer];
-        value
+        Ok(self.stack[self.stack_pointer])
     }
 
     #[inline]
@@ -120,7 +398,7 @@ impl Runtime {
     }
 
     #[inline]
-    pub fn execute(&mut self, instruction: Instruction) -> Option<Value> {
+    pub fn execute(&mut self, instruction: Instruction) -> Result<(), RuntimeError> {
         if self.stack_pointer + STACK_THRESHOLD >= self.stack.len() {
             self.stack
                 .resize(self.stack.len() + STACK_SIZE, Value::Int(0));
@@ -132,15 +410,40 @@ impl Runtime {
             }
             Instruction::ExternCall { string_id } => {
                 let name = &*self.strings[string_id];
-                let function = match self.functions.get(name) {
-                    Some(function) => function,
-                    None => panic!("Function '{}' not found", name),
+                let native = match self.functions.get(name) {
+                    Some(native) => native.clone(),
+                    None => return Err(RuntimeError::FunctionNotFound(name.to_string())),
                 };
 
-                function(self);
+                let count = match self.stack_pop()? {
+                    Value::Int(count) => count as usize,
+                    found => {
+                        return Err(RuntimeError::TypeMismatch {
+                            expected: "int",
+                            found,
+                        })
+                    }
+                };
+
+                if count < native.arity || (count > native.arity && !native.variadic) {
+                    return Err(RuntimeError::ArgumentCount {
+                        function: self.strings[string_id].clone(),
+                        expected: native.arity,
+                        found: count,
+                    });
+                }
+
+                let mut args = Vec::with_capacity(count);
+                for _ in 0..count {
+                    args.push(self.stack_pop()?);
+                }
+
+                if let Some(value) = (native.function)(self, args)? {
+                    self.stack_push(value);
+                }
             }
             Instruction::Store { address } => {
-                let value = self.stack_pop();
+                let value = self.stack_pop()?;
                 self.heap[address] = value;
             }
             Instruction::Load { address } => {
@@ -148,222 +451,158 @@ impl Runtime {
                 self.stack_push(value);
             }
             Instruction::Add => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
 
-                match (left, right) {
-                    (Value::Int(left), Value::Int(right)) => {
-                        self.stack_push(Value::Int(left + right));
-                    }
-                    (Value::Float(left), Value::Float(right)) => {
-                        self.stack_push(Value::Float(left + right));
-                    }
+                let value = match (left, right) {
+                    (Value::Int(left), Value::Int(right)) => match left.checked_add(right) {
+                        Some(value) => Value::Int(value),
+                        None => return Err(RuntimeError::IntegerOverflow),
+                    },
+                    (Value::Float(left), Value::Float(right)) => Value::Float(left + right),
                     (Value::Char(left), Value::Char(right)) => {
-                        let new_string = left.to_string() + &right.to_string();
-                        let str_ref = self.new_string(new_string);
-                        self.stack_push(str_ref);
+                        self.new_string(left.to_string() + &right.to_string())
                     }
                     (Value::StrRef(left), Value::StrRef(right)) => {
-                        let left = self.string_objects.get(&left).unwrap();
-                        let right = self.string_objects.get(&right).unwrap();
-
-                        let new_string = left.to_string() + right;
-                        let str_ref = self.new_string(new_string);
-                        self.stack_push(str_ref);
+                        let new_string =
+                            self.string_object(left)?.clone() + self.string_object(right)?;
+                        self.new_string(new_string)
                     }
                     (Value::StrRef(left), Value::Char(right)) => {
-                        let left = self.string_objects.get(&left).unwrap();
-
-                        let new_string = left.to_string() + &right.to_string();
-                        let str_ref = self.new_string(new_string);
-                        self.stack_push(str_ref);
+                        let new_string = self.string_object(left)?.clone() + &right.to_string();
+                        self.new_string(new_string)
                     }
                     (Value::Char(left), Value::StrRef(right)) => {
-                        let right = self.string_objects.get(&right).unwrap();
-
-                        let new_string = left.to_string() + &right;
-                        let str_ref = self.new_string(new_string);
-                        self.stack_push(str_ref);
+                        let new_string = left.to_string() + self.string_object(right)?;
+                        self.new_string(new_string)
                     }
                     (Value::ListRef(left), Value::ListRef(right)) => {
-                        let mut list = self.lists.get(&left).unwrap().clone();
-                        list.extend(self.lists.get(&right).unwrap().clone());
-                        self.stack_push(Value::ListRef(left));
+                        let mut list = self.list_object(left)?.clone();
+                        list.extend(self.list_object(right)?.iter().copied());
+                        self.new_list(list)
                     }
-                    _ => {
-                        panic!("Expected two values of the same type")
+                    (left, right) => {
+                        return Err(operand_mismatch(
+                            "int or float or char or string or list",
+                            left,
+                            right,
+                        ))
                     }
-                }
+                };
+                self.stack_push(value);
             }
             Instruction::Sub => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
-                self.stack_push(bin_op_2!(left, right, -));
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
+                self.stack_push(bin_op_2!(left, right, checked_sub, -));
             }
             Instruction::Mul => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
-                self.stack_push(bin_op_2!(left, right, *));
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
+                self.stack_push(bin_op_2!(left, right, checked_mul, *));
             }
             Instruction::Div => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
-                self.stack_push(bin_op_2!(left, right, /));
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
+                self.stack_push(bin_op_2!(left, right, checked_div, /));
             }
             Instruction::Mod => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
-                self.stack_push(bin_op_2!(left, right, %));
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
+                self.stack_push(bin_op_2!(left, right, checked_rem, %));
             }
             Instruction::Pow => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
 
-                match (left, right) {
+                let value = match (left, right) {
                     (Value::Int(left), Value::Int(right)) => {
-                        self.stack_push(Value::Int(left.pow(right as u32)));
-                    }
-                    (Value::Float(left), Value::Float(right)) => {
-                        self.stack_push(Value::Float(left.powf(right)));
-                    }
-                    _ => {
-                        panic!("Expected two values of the same type")
+                        // a negative exponent has no integer result either
+                        match u32::try_from(right)
+                            .ok()
+                            .and_then(|right| left.checked_pow(right))
+                        {
+                            Some(value) => Value::Int(value),
+                            None => return Err(RuntimeError::IntegerOverflow),
+                        }
                     }
-                }
+                    (Value::Float(left), Value::Float(right)) => Value::Float(left.powf(right)),
+                    (left, right) => return Err(operand_mismatch("int or float", left, right)),
+                };
+                self.stack_push(value);
             }
             Instruction::Ret => {
-                self.instruction_pointer = self.call_stack.pop().unwrap();
+                self.instruction_pointer = match self.call_stack.pop() {
+                    Some(address) => address,
+                    None => return Err(RuntimeError::ReturnOutsideFunction),
+                };
             }
             Instruction::Gt => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
                 self.stack_push(bin_op_2_comp!(left, right, >));
             }
             Instruction::Lt => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
                 self.stack_push(bin_op_2_comp!(left, right, <));
             }
             Instruction::Gte => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
                 self.stack_push(bin_op_2_comp!(left, right, >=));
             }
             Instruction::Lte => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
                 self.stack_push(bin_op_2_comp!(left, right, <=));
             }
             Instruction::Eq => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
-
-                match (left, right) {
-                    (Value::Char(left), Value::Char(right)) => {
-                        self.stack_push(Value::Bool(left == right));
-                    }
-                    (Value::Int(left), Value::Int(right)) => {
-                        self.stack_push(Value::Bool(left == right));
-                    }
-                    (Value::Float(left), Value::Float(right)) => {
-                        self.stack_push(Value::Bool(left == right));
-                    }
-                    (Value::StrRef(left), Value::StrRef(right)) => {
-                        let left = self.string_objects.get(&left).unwrap();
-                        let right = self.string_objects.get(&right).unwrap();
-
-                        self.stack_push(Value::Bool(left == right));
-                    }
-                    (Value::Bool(left), Value::Bool(right)) => {
-                        self.stack_push(Value::Bool(left == right));
-                    }
-                    (Value::StrRef(left), Value::Char(right)) => {
-                        let left = self.string_objects.get(&left).unwrap();
-
-                        self.stack_push(Value::Bool(left == &right.to_string()));
-                    }
-                    (Value::Char(left), Value::StrRef(right)) => {
-                        let right = self.string_objects.get(&right).unwrap();
-
-                        self.stack_push(Value::Bool(&left.to_string() == right));
-                    }
-                    (Value::ListRef(left), Value::ListRef(right)) => {
-                        let left = self.lists.get(&left).unwrap();
-                        let right = self.lists.get(&right).unwrap();
-
-                        self.stack_push(Value::Bool(left == right));
-                    }
-                    _ => {
-                        panic!("Expected two values of the same type")
-                    }
-                }
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
+                let equal = self.values_equal(left, right)?;
+                self.stack_push(Value::Bool(equal));
             }
             Instruction::Neq => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
-
-                match (left, right) {
-                    (Value::Int(left), Value::Int(right)) => {
-                        self.stack_push(Value::Bool(left != right));
-                    }
-                    (Value::Float(left), Value::Float(right)) => {
-                        self.stack_push(Value::Bool(left != right));
-                    }
-                    (Value::StrRef(left), Value::StrRef(right)) => {
-                        let left = self.string_objects.get(&left).unwrap();
-                        let right = self.string_objects.get(&right).unwrap();
-
-                        self.stack_push(Value::Bool(left != right));
-                    }
-                    (Value::Bool(left), Value::Bool(right)) => {
-                        self.stack_push(Value::Bool(left != right));
-                    }
-                    (Value::ListRef(left), Value::ListRef(right)) => {
-                        let left = self.lists.get(&left).unwrap();
-                        let right = self.lists.get(&right).unwrap();
-
-                        self.stack_push(Value::Bool(left != right));
-                    }
-                    _ => {
-                        panic!("Expected two values of the same type")
-                    }
-                }
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
+                let equal = self.values_equal(left, right)?;
+                self.stack_push(Value::Bool(!equal));
             }
             Instruction::And => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
 
                 match (left, right) {
                     (Value::Bool(left), Value::Bool(right)) => {
                         self.stack_push(Value::Bool(left && right));
                     }
-                    _ => {
-                        panic!("Expected two values of the same type")
-                    }
+                    (left, right) => return Err(operand_mismatch("bool", left, right)),
                 }
             }
             Instruction::Or => {
-                let right = self.stack_pop();
-                let left = self.stack_pop();
+                let right = self.stack_pop()?;
+                let left = self.stack_pop()?;
 
                 match (left, right) {
                     (Value::Bool(left), Value::Bool(right)) => {
                         self.stack_push(Value::Bool(left || right));
                     }
-                    _ => {
-                        panic!("Expected two values of the same type")
-                    }
+                    (left, right) => return Err(operand_mismatch("bool", left, right)),
                 }
             }
             Instruction::Not => {
-                let value = self.stack_pop();
+                let value = self.stack_pop()?;
 
                 match value {
                     Value::Bool(value) => {
                         self.stack_push(Value::Bool(!value));
                     }
-                    _ => {
-                        panic!("Expected two values of the same type")
+                    found => {
+                        return Err(RuntimeError::TypeMismatch {
+                            expected: "bool",
+                            found,
+                        })
                     }
                 }
             }
@@ -371,7 +610,7 @@ impl Runtime {
                 self.instruction_pointer = address;
             }
             Instruction::JumpIfTrue { address } => {
-                let value = self.stack_pop();
+                let value = self.stack_pop()?;
 
                 match value {
                     Value::Bool(value) => {
@@ -379,13 +618,16 @@ impl Runtime {
                             self.instruction_pointer = address;
                         }
                     }
-                    _ => {
-                        panic!("Expected two values of the same type")
+                    found => {
+                        return Err(RuntimeError::TypeMismatch {
+                            expected: "bool",
+                            found,
+                        })
                     }
                 }
             }
             Instruction::JumpIfFalse { address } => {
-                let value = self.stack_pop();
+                let value = self.stack_pop()?;
 
                 match value {
                     Value::Bool(value) => {
@@ -393,8 +635,11 @@ impl Runtime {
                             self.instruction_pointer = address;
                         }
                     }
-                    _ => {
-                        panic!("Expected two values of the same type")
+                    found => {
+                        return Err(RuntimeError::TypeMismatch {
+                            expected: "bool",
+                            found,
+                        })
                     }
                 }
             }
@@ -404,19 +649,14 @@ impl Runtime {
                 self.instruction_pointer = address;
             }
             Instruction::AccessMember { index } => {
-                let object = self.stack_pop();
-                let object = self.objects.get(&object.as_object_ref().unwrap()).unwrap();
-                let value = object.members[index].clone();
+                let object = self.stack_pop()?;
+                let value = *self.member(object, index)?;
                 self.stack_push(value);
             }
             Instruction::SetMember { index } => {
-                let value = self.stack_pop();
-                let object = self.stack_pop();
-                let object = self
-                    .objects
-                    .get_mut(&object.as_object_ref().unwrap())
-                    .unwrap();
-                object.members[index] = value;
+                let value = self.stack_pop()?;
+                let object = self.stack_pop()?;
+                *self.member(object, index)? = value;
             }
             Instruction::CreateObject { descriptor } => {
                 let object_id = self.object_init_counter;
@@ -426,7 +666,7 @@ impl Runtime {
                 let mut members = vec![];
 
                 for _ in 0..descriptor.members.len() {
-                    let val = self.stack_pop();
+                    let val = self.stack_pop()?;
                     members.push(val);
                 }
 
@@ -439,16 +679,454 @@ impl Runtime {
                 self.stack_push(Value::ObjectRef(object_id));
             }
         }
-        None
+        Ok(())
     }
 
-    pub fn run(&mut self) {
+    pub fn enable_profiling(&mut self) {
+        let mut names = self
+            .exports
+            .iter()
+            .map(|(name, export)| (export.address, name.clone()))
+            .collect::<HashMap<usize, String>>();
+
+        if let Some(debug) = &self.debug {
+            for function in debug.functions.iter() {
+                names.insert(function.start, function.name.clone());
+            }
+        }
+
+        self.profiler = Some(Profiler::new().with_function_names(names));
+    }
+
+    /// Addresses of the instruction that last ran and of every call leading to it, innermost first
+    pub fn backtrace(&self) -> Vec<usize> {
+        std::iter::once(self.instruction_pointer)
+            .chain(self.call_stack.iter().rev().copied())
+            .map(|address| address.saturating_sub(1))
+            .collect()
+    }
+
+    pub fn run(&mut self) -> Result<(), RuntimeError> {
+        if self.profiler.is_some() {
+            return self.run_profiled();
+        }
+
         let instructions_length = self.instructions.len();
         while self.instruction_pointer < instructions_length {
             let instruction = self.instructions[self.instruction_pointer];
             self.instruction_pointer += 1;
 
-            self.execute(instruction);
+            self.execute(instruction)?;
+        }
+
+        Ok(())
+    }
+
+    fn run_profiled(&mut self) -> Result<(), RuntimeError> {
+        let mut profiler = self.profiler.take().unwrap();
+        profiler.start(self.instructions.len());
+
+        let mut result = Ok(());
+        let instructions_length = self.instructions.len();
+        while self.instruction_pointer < instructions_length {
+            let address = self.instruction_pointer;
+            let instruction = self.instructions[address];
+            self.instruction_pointer += 1;
+
+            profiler.record(address, &instruction);
+            result = self.execute(instruction);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        profiler.finish();
+        self.profiler = Some(profiler);
+
+        result
+    }
+
+    /// Calls an exported `proseso` by name and runs it until it returns
+    ///
+    /// Functions that end without `ibalik` return `Value::Null`. Globals are only initialized once
+    /// the top level code has been executed with `run`.
+    ///
+    /// # Example
+    /// ```
+    /// use bincore::data::function::FunctionExport;
+    /// use bincore::data::program_file::Program;
+    /// use bincore::data::value::Value;
+    /// use bincore::executable::runnable::Instruction;
+    ///
+    /// // proseso square(x) { ibalik x * x; }
+    /// let program = Program {
+    ///     instructions: vec![
+    ///         Instruction::Nop,
+    ///         Instruction::Load { address: 0 },
+    ///         Instruction::Load { address: 0 },
+    ///         Instruction::Mul,
+    ///         Instruction::Ret,
+    ///     ],
+    ///     strings: vec![],
+    ///     heap_size: 1,
+    ///     object_descriptor: vec![],
+    ///     exports: [(
+    ///         "square".to_string(),
+    ///         FunctionExport { address: 1, args: vec![0] },
+    ///     )]
+    ///     .into(),
+    ///     debug: None,
+    /// };
+    ///
+    /// let mut runtime = program.into_runtime();
+    /// let result = runtime.call_function("square", &[Value::Int(7)]).unwrap();
+    ///
+    /// assert_eq!(runtime.from_value::<i64>(result), Ok(49));
+    /// ```
+    pub fn call_function(&mut self, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        let export = match self.exports.get(name) {
+            Some(export) => export.clone(),
+            None => return Err(RuntimeError::FunctionNotFound(name.to_string())),
+        };
+
+        if export.args.len() != args.len() {
+            return Err(RuntimeError::ArgumentCount {
+                function: name.to_string(),
+                expected: export.args.len(),
+                found: args.len(),
+            });
         }
+
+        for (address, value) in export.args.iter().zip(args) {
+            self.heap[*address] = *value;
+        }
+
+        let stack_base = self.stack_pointer;
+        let call_depth = self.call_stack.len();
+
+        let return_address = self.instruction_pointer;
+        self.call_stack.push(return_address);
+        self.instruction_pointer = export.address;
+
+        while self.call_stack.len() > call_depth {
+            let result = match self.instructions.get(self.instruction_pointer) {
+                Some(instruction) => {
+                    let instruction = *instruction;
+                    self.instruction_pointer += 1;
+                    self.execute(instruction)
+                }
+                None => Err(RuntimeError::InstructionOutOfBounds(
+                    self.instruction_pointer,
+                )),
+            };
+
+            if let Err(error) = result {
+                self.call_stack.truncate(call_depth);
+                self.stack_pointer = stack_base;
+                self.instruction_pointer = return_address;
+                return Err(error);
+            }
+        }
+
+        if self.stack_pointer > stack_base {
+            let value = self.stack_pop()?;
+            self.stack_pointer = stack_base;
+            Ok(value)
+        } else {
+            Ok(Value::Null)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly::assembler::assemble;
+    use crate::executable::native::Variadic;
+
+    /// Runs `code` after the `nop` at address 0 with one heap slot
+    fn run(code: &str) -> Result<Runtime, RuntimeError> {
+        let program = assemble(&format!(".heap 1\n.code\n    nop\n{}", code)).unwrap();
+        let mut runtime = program.into_runtime();
+        runtime.run()?;
+        Ok(runtime)
+    }
+
+    fn run_error(code: &str) -> RuntimeError {
+        match run(code) {
+            Ok(_) => panic!("expected {:?} to fail", code),
+            Err(error) => error,
+        }
+    }
+
+    #[test]
+    fn operands_of_other_types_are_errors() {
+        assert_eq!(
+            run_error("    push int 1\n    push str \"a\"\n    add"),
+            RuntimeError::TypeMismatch {
+                expected: "int",
+                found: Value::StrRef(0),
+            }
+        );
+        assert_eq!(
+            run_error("    push bool true\n    push int 1\n    sub"),
+            RuntimeError::TypeMismatch {
+                expected: "int or float",
+                found: Value::Bool(true),
+            }
+        );
+        assert_eq!(
+            run_error("    push float 1.0\n    push int 1\n    lt"),
+            RuntimeError::TypeMismatch {
+                expected: "float",
+                found: Value::Int(1),
+            }
+        );
+        assert_eq!(
+            run_error("    push int 1\n    push bool true\n    eq"),
+            RuntimeError::TypeMismatch {
+                expected: "int",
+                found: Value::Bool(true),
+            }
+        );
+        assert_eq!(
+            run_error("    push int 0\n    not"),
+            RuntimeError::TypeMismatch {
+                expected: "bool",
+                found: Value::Int(0),
+            }
+        );
+        assert_eq!(
+            run_error("    push null\n    jump_if_false 0"),
+            RuntimeError::TypeMismatch {
+                expected: "bool",
+                found: Value::Null,
+            }
+        );
+    }
+
+    #[test]
+    fn integer_arithmetic_is_checked() {
+        assert_eq!(
+            run_error("    push int 1\n    push int 0\n    div"),
+            RuntimeError::DivisionByZero
+        );
+        assert_eq!(
+            run_error("    push int 1\n    push int 0\n    mod"),
+            RuntimeError::DivisionByZero
+        );
+        assert_eq!(
+            run_error(&format!(
+                "    push int {}\n    push int 1\n    add",
+                i64::MAX
+            )),
+            RuntimeError::IntegerOverflow
+        );
+        assert_eq!(
+            run_error("    push int 2\n    push int -1\n    pow"),
+            RuntimeError::IntegerOverflow
+        );
+
+        let runtime = run("    push int 2\n    push int 10\n    pow\n    store 0").unwrap();
+        assert_eq!(runtime.heap[0], Value::Int(1024));
+    }
+
+    #[test]
+    fn equality_compares_contents() {
+        let runtime = run("
+            push str \"ab\"
+            push char 'a'
+            push char 'b'
+            add
+            eq
+            store 0
+        ")
+        .unwrap();
+        assert_eq!(runtime.heap[0], Value::Bool(true));
+
+        let runtime = run("    push null\n    push int 1\n    neq\n    store 0").unwrap();
+        assert_eq!(runtime.heap[0], Value::Bool(true));
+    }
+
+    #[test]
+    fn references_to_nothing_are_errors() {
+        assert_eq!(
+            run_error("    push str 0\n    push list 3\n    push list 4\n    add"),
+            RuntimeError::InvalidReference(Value::ListRef(3))
+        );
+        assert_eq!(
+            run_error("    push object 7\n    access_member 0"),
+            RuntimeError::InvalidReference(Value::ObjectRef(7))
+        );
+        assert_eq!(
+            run_error("    push int 7\n    push int 1\n    set_member 0"),
+            RuntimeError::TypeMismatch {
+                expected: "object",
+                found: Value::Int(7),
+            }
+        );
+        assert_eq!(run_error("    ret"), RuntimeError::ReturnOutsideFunction);
+    }
+
+    #[test]
+    fn members_past_the_descriptor_are_errors() {
+        assert_eq!(
+            run_error(
+                "
+                .object \"Punto\" \"x\"
+                .code
+                    push int 1
+                    create_object 0
+                    access_member 1
+                "
+            ),
+            RuntimeError::IndexOutOfBounds {
+                index: 1,
+                length: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn popping_an_empty_stack_is_an_error() {
+        assert_eq!(
+            run_error("    push int 1\n    add"),
+            RuntimeError::StackUnderflow
+        );
+        assert_eq!(
+            Runtime::new().stack_pop(),
+            Err(RuntimeError::StackUnderflow)
+        );
+    }
+
+    #[test]
+    fn natives_check_their_arguments() {
+        let call_doble = |arguments: &str| {
+            let code = format!(".code\n    nop\n{}\n    extern_call \"doble\"", arguments);
+            let mut runtime = assemble(&code).unwrap().into_runtime();
+            runtime.register_native("doble", |_: &mut Runtime, value: i64| value * 2);
+            runtime.run().and_then(|_| runtime.stack_pop())
+        };
+
+        assert_eq!(
+            call_doble("    push int 4\n    push int 1"),
+            Ok(Value::Int(8))
+        );
+        assert_eq!(
+            call_doble("    push int 4\n    push int 4\n    push int 2"),
+            Err(RuntimeError::ArgumentCount {
+                function: "doble".to_string(),
+                expected: 1,
+                found: 2,
+            })
+        );
+        assert_eq!(
+            call_doble("    push bool true\n    push int 1"),
+            Err(RuntimeError::TypeMismatch {
+                expected: "int",
+                found: Value::Bool(true),
+            })
+        );
+    }
+
+    #[test]
+    fn variadic_natives_take_every_argument() {
+        let mut runtime = Runtime::new();
+        runtime.register_native("sumada", |_: &mut Runtime, values: Variadic<i64>| {
+            values.0.iter().sum::<i64>()
+        });
+
+        let sumada = runtime.functions["sumada"].function.clone();
+        let sum = sumada(
+            &mut runtime,
+            vec![Value::Int(1), Value::Int(2), Value::Int(3)],
+        );
+        assert_eq!(sum, Ok(Some(Value::Int(6))));
+    }
+
+    #[test]
+    fn reload_keeps_strings_made_while_running() {
+        let program = assemble(
+            r#"
+            .heap 1
+            .string "x"
+            .string "y"
+            .code
+                nop
+                push str 0
+                push str 1
+                add
+                store 0
+            "#,
+        )
+        .unwrap();
+        let mut runtime = program.into_runtime();
+        runtime.run().unwrap();
+        assert_eq!(runtime.heap[0], Value::StrRef(2));
+
+        // the new constant has the id the string made by `add` already holds
+        let appended = assemble(
+            r#"
+            .heap 2
+            .string "x"
+            .string "y"
+            .string "z"
+            .code
+                nop
+                push str 0
+                push str 1
+                add
+                store 0
+                push str 2
+                store 1
+            "#,
+        )
+        .unwrap();
+        runtime.reload(&appended);
+        runtime.instruction_pointer = 5;
+        runtime.run().unwrap();
+
+        let strings = runtime
+            .heap
+            .iter()
+            .map(|value| runtime.from_value::<String>(*value).unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(strings, vec!["xy", "z"]);
+    }
+
+    #[test]
+    fn reload_takes_over_new_object_descriptors() {
+        let program = assemble(".heap 1\n.code\n    nop").unwrap();
+        let mut runtime = program.into_runtime();
+        runtime.run().unwrap();
+
+        let appended = assemble(
+            r#"
+            .heap 1
+            .object "Punto" "x"
+            .code
+                nop
+                push int 7
+                create_object 0
+                access_member 0
+                store 0
+            "#,
+        )
+        .unwrap();
+        runtime.reload(&appended);
+        runtime.instruction_pointer = 1;
+        runtime.run().unwrap();
+
+        assert_eq!(runtime.heap[0], Value::Int(7));
+    }
+
+    #[test]
+    fn reload_reuses_unchanged_constants() {
+        let program = assemble(".string \"x\"\n.code\n    push str 0").unwrap();
+        let mut runtime = program.clone().into_runtime();
+        runtime.reload(&program);
+
+        assert_eq!(runtime.string_constants, vec![0]);
+        assert_eq!(runtime.string_objects.len(), 1);
     }
 }
@@ -0,0 +1,579 @@
+use crate::data::program_file::Program;
+use crate::data::value::Value;
+use crate::executable::runnable::Instruction;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// Address `Runtime::run` starts executing top level code at
+pub const ENTRY_ADDRESS: usize = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyError {
+    JumpOutOfBounds {
+        address: usize,
+        target: usize,
+    },
+    HeapOutOfBounds {
+        address: usize,
+        slot: usize,
+    },
+    StringOutOfBounds {
+        address: usize,
+        string_id: usize,
+    },
+    DescriptorOutOfBounds {
+        address: usize,
+        descriptor: usize,
+    },
+    MemberOutOfBounds {
+        address: usize,
+        index: usize,
+    },
+    InvalidConstant {
+        address: usize,
+        value: Value,
+    },
+    MissingArgumentCount {
+        address: usize,
+    },
+    StackUnderflow {
+        address: usize,
+        needed: usize,
+        available: usize,
+    },
+    InconsistentStack {
+        address: usize,
+        first: usize,
+        second: usize,
+    },
+    ReturnOutsideFunction {
+        address: usize,
+    },
+    InvalidExport {
+        function: String,
+    },
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::JumpOutOfBounds { address, target } => write!(
+                f,
+                "{}: jump target {} is outside of the program",
+                address, target
+            ),
+            VerifyError::HeapOutOfBounds { address, slot } => {
+                write!(f, "{}: heap slot {} is out of bounds", address, slot)
+            }
+            VerifyError::StringOutOfBounds { address, string_id } => {
+                write!(f, "{}: string {} is out of bounds", address, string_id)
+            }
+            VerifyError::DescriptorOutOfBounds {
+                address,
+                descriptor,
+            } => write!(
+                f,
+                "{}: object descriptor {} is out of bounds",
+                address, descriptor
+            ),
+            VerifyError::MemberOutOfBounds { address, index } => write!(
+                f,
+                "{}: member {} is out of bounds for every object descriptor",
+                address, index
+            ),
+            VerifyError::InvalidConstant { address, value } => {
+                write!(f, "{}: {:?} cannot be a constant", address, value)
+            }
+            VerifyError::MissingArgumentCount { address } => write!(
+                f,
+                "{}: extern call is not preceded by its argument count",
+                address
+            ),
+            VerifyError::StackUnderflow {
+                address,
+                needed,
+                available,
+            } => write!(
+                f,
+                "{}: needs {} value(s) on the stack but at most {} can be there",
+                address, needed, available
+            ),
+            VerifyError::InconsistentStack {
+                address,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}: reached with a stack height of both {} and {}",
+                address, first, second
+            ),
+            VerifyError::ReturnOutsideFunction { address } => {
+                write!(f, "{}: return outside of a function", address)
+            }
+            VerifyError::InvalidExport { function } => write!(
+                f,
+                "export '{}' points outside of the program or the heap",
+                function
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Checks every operand of `program` and that no path through it underflows the stack
+///
+/// Heights are tracked per function as a range, starting from zero at `ENTRY_ADDRESS` and at
+/// every call target. A call or an extern call may or may not leave a return value behind, so an
+/// underflow is only reported when no path could have the values an instruction pops. Paths with
+/// exact heights have to agree where they meet.
+///
+/// # Example
+/// ```
+/// use bincore::data::program_file::Program;
+/// use bincore::executable::runnable::Instruction;
+/// use bincore::executable::verifier::{verify, VerifyError};
+///
+/// let program = Program {
+///     instructions: vec![Instruction::Nop, Instruction::Load { address: 3 }],
+///     heap_size: 1,
+///     ..Program::default()
+/// };
+///
+/// assert_eq!(
+///     verify(&program),
+///     Err(vec![VerifyError::HeapOutOfBounds { address: 1, slot: 3 }])
+/// );
+/// ```
+pub fn verify(program: &Program) -> Result<(), Vec<VerifyError>> {
+    let mut errors = vec![];
+
+    check_operands(program, &mut errors);
+    check_exports(program, &mut errors);
+
+    // heights are meaningless once a jump can leave the program
+    if errors.is_empty() {
+        let mut functions = program
+            .instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call { address } => Some(*address),
+                _ => None,
+            })
+            .chain(program.exports.values().map(|export| export.address))
+            .collect::<BTreeSet<usize>>();
+        functions.remove(&ENTRY_ADDRESS);
+
+        if ENTRY_ADDRESS < program.instructions.len() {
+            check_stack(program, ENTRY_ADDRESS, true, &mut errors);
+        }
+        for function in functions {
+            check_stack(program, function, false, &mut errors);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_operands(program: &Program, errors: &mut Vec<VerifyError>) {
+    let length = program.instructions.len();
+    let max_members = program
+        .object_descriptor
+        .iter()
+        .map(|descriptor| descriptor.members_by_index.len())
+        .max()
+        .unwrap_or(0);
+
+    for (address, instruction) in program.instructions.iter().enumerate() {
+        match *instruction {
+            Instruction::Push { value } => match value {
+                Value::StrRef(string_id) if string_id >= program.strings.len() => {
+                    errors.push(VerifyError::StringOutOfBounds { address, string_id })
+                }
+                Value::ListRef(_) | Value::ObjectRef(_) => {
+                    errors.push(VerifyError::InvalidConstant { address, value })
+                }
+                _ => {}
+            },
+            Instruction::ExternCall { string_id } => {
+                if string_id >= program.strings.len() {
+                    errors.push(VerifyError::StringOutOfBounds { address, string_id });
+                }
+                if argument_count(program, address).is_none() {
+                    errors.push(VerifyError::MissingArgumentCount { address });
+                }
+            }
+            Instruction::Store { address: slot } | Instruction::Load { address: slot }
+                if slot >= program.heap_size =>
+            {
+                errors.push(VerifyError::HeapOutOfBounds { address, slot });
+            }
+            Instruction::AccessMember { index } | Instruction::SetMember { index }
+                if index >= max_members =>
+            {
+                errors.push(VerifyError::MemberOutOfBounds { address, index });
+            }
+            Instruction::Call { address: target }
+            | Instruction::Jump { address: target }
+            | Instruction::JumpIfTrue { address: target }
+            | Instruction::JumpIfFalse { address: target }
+                if target >= length =>
+            {
+                errors.push(VerifyError::JumpOutOfBounds { address, target });
+            }
+            Instruction::CreateObject { descriptor }
+                if descriptor >= program.object_descriptor.len() =>
+            {
+                errors.push(VerifyError::DescriptorOutOfBounds {
+                    address,
+                    descriptor,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_exports(program: &Program, errors: &mut Vec<VerifyError>) {
+    let mut functions = program.exports.iter().collect::<Vec<_>>();
+    functions.sort_by_key(|(name, _)| name.as_str());
+
+    for (name, export) in functions {
+        if export.address >= program.instructions.len()
+            || export.args.iter().any(|slot| *slot >= program.heap_size)
+        {
+            errors.push(VerifyError::InvalidExport {
+                function: name.clone(),
+            });
+        }
+    }
+}
+
+/// The `Push { Int }` in front of an `ExternCall`, as emitted by the compiler
+fn argument_count(program: &Program, address: usize) -> Option<usize> {
+    match program.instructions.get(address.checked_sub(1)?)? {
+        Instruction::Push {
+            value: Value::Int(count),
+        } if *count >= 0 => Some(*count as usize),
+        _ => None,
+    }
+}
+
+/// The possible stack heights at an instruction, relative to the start of its function
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Height {
+    low: usize,
+    /// `None` when a loop may keep growing the stack, e.g. by calling in statement position
+    high: Option<usize>,
+}
+
+impl Height {
+    fn exact(&self) -> Option<usize> {
+        match self.high {
+            Some(high) if high == self.low => Some(high),
+            _ => None,
+        }
+    }
+}
+
+fn check_stack(program: &Program, root: usize, top_level: bool, errors: &mut Vec<VerifyError>) {
+    let length = program.instructions.len();
+    let mut heights: Vec<Option<Height>> = vec![None; length];
+    heights[root] = Some(Height {
+        low: 0,
+        high: Some(0),
+    });
+
+    let mut pending = vec![root];
+    while let Some(address) = pending.pop() {
+        let height = heights[address].unwrap();
+        let instruction = program.instructions[address];
+
+        // calls push a return value only if the callee has one, which is not known here
+        let (pops, pushes, may_push) = match instruction {
+            Instruction::Nop | Instruction::Jump { .. } | Instruction::Ret => (0, 0, 0),
+            Instruction::Push { .. } | Instruction::Load { .. } => (0, 1, 0),
+            Instruction::Store { .. }
+            | Instruction::JumpIfTrue { .. }
+            | Instruction::JumpIfFalse { .. } => (1, 0, 0),
+            Instruction::AccessMember { .. } | Instruction::Not => (1, 1, 0),
+            Instruction::SetMember { .. } => (2, 0, 0),
+            Instruction::Add
+            | Instruction::Sub
+            | Instruction::Mul
+            | Instruction::Div
+            | Instruction::Mod
+            | Instruction::Pow
+            | Instruction::Gt
+            | Instruction::Lt
+            | Instruction::Gte
+            | Instruction::Lte
+            | Instruction::Eq
+            | Instruction::Neq
+            | Instruction::And
+            | Instruction::Or => (2, 1, 0),
+            Instruction::CreateObject { descriptor } => {
+                (program.object_descriptor[descriptor].members.len(), 1, 0)
+            }
+            Instruction::Call { .. } => (0, 0, 1),
+            Instruction::ExternCall { .. } => (1 + argument_count(program, address).unwrap(), 0, 1),
+        };
+
+        // only `low` values are sure to be there once a loop made the height unbounded
+        let available = height.high.unwrap_or(height.low);
+        if pops > available {
+            push_unique(
+                errors,
+                VerifyError::StackUnderflow {
+                    address,
+                    needed: pops,
+                    available,
+                },
+            );
+            continue;
+        }
+
+        let after = Height {
+            low: height.low.saturating_sub(pops) + pushes,
+            high: height.high.map(|high| high - pops + pushes + may_push),
+        };
+
+        let successors = match instruction {
+            Instruction::Ret => {
+                if top_level {
+                    push_unique(errors, VerifyError::ReturnOutsideFunction { address });
+                }
+                vec![]
+            }
+            Instruction::Jump { address: target } => vec![target],
+            Instruction::JumpIfTrue { address: target }
+            | Instruction::JumpIfFalse { address: target } => vec![target, address + 1],
+            _ => vec![address + 1],
+        };
+
+        for successor in successors {
+            // running past the last instruction ends the program
+            if successor >= length {
+                continue;
+            }
+
+            let merged = match heights[successor] {
+                None => after,
+                Some(existing) => {
+                    if let (Some(first), Some(second)) = (existing.exact(), after.exact()) {
+                        if first != second {
+                            push_unique(
+                                errors,
+                                VerifyError::InconsistentStack {
+                                    address: successor,
+                                    first,
+                                    second,
+                                },
+                            );
+                        }
+                    }
+
+                    // a height that keeps growing at an instruction seen before is unbounded,
+                    // which keeps loops from being walked forever
+                    let high = match (existing.high, after.high) {
+                        (Some(existing), Some(after)) if after <= existing => Some(existing),
+                        _ => None,
+                    };
+
+                    Height {
+                        low: existing.low.min(after.low),
+                        high,
+                    }
+                }
+            };
+
+            if heights[successor] != Some(merged) {
+                heights[successor] = Some(merged);
+                pending.push(successor);
+            }
+        }
+    }
+}
+
+fn push_unique(errors: &mut Vec<VerifyError>, error: VerifyError) {
+    if !errors.contains(&error) {
+        errors.push(error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly::assembler::assemble;
+
+    fn verify_code(code: &str) -> Result<(), Vec<VerifyError>> {
+        let program = assemble(&format!(".heap 1\n.code\n    nop\n{}", code)).unwrap();
+        verify(&program)
+    }
+
+    #[test]
+    fn accepts_balanced_code() {
+        assert_eq!(
+            verify_code(
+                "
+                push int 1
+                push int 2
+                add
+                store 0
+                push str \"x\"
+                push int 1
+                extern_call \"ipakita\"
+                "
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn reports_bad_operands() {
+        let mut program = assemble(".heap 1\n.code\n    nop\n    push list 0\n    load 4").unwrap();
+        program.instructions.push(Instruction::Jump { address: 9 });
+        program.instructions.push(Instruction::Push {
+            value: Value::StrRef(2),
+        });
+
+        assert_eq!(
+            verify(&program),
+            Err(vec![
+                VerifyError::InvalidConstant {
+                    address: 1,
+                    value: Value::ListRef(0),
+                },
+                VerifyError::HeapOutOfBounds {
+                    address: 2,
+                    slot: 4,
+                },
+                VerifyError::JumpOutOfBounds {
+                    address: 3,
+                    target: 9,
+                },
+                VerifyError::StringOutOfBounds {
+                    address: 4,
+                    string_id: 2,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn extern_calls_need_an_argument_count() {
+        assert_eq!(
+            verify_code("    extern_call \"ipakita\""),
+            Err(vec![VerifyError::MissingArgumentCount { address: 1 }])
+        );
+    }
+
+    #[test]
+    fn reports_underflow() {
+        assert_eq!(
+            verify_code("    push int 1\n    add"),
+            Err(vec![VerifyError::StackUnderflow {
+                address: 2,
+                needed: 2,
+                available: 1,
+            }])
+        );
+    }
+
+    #[test]
+    fn reports_paths_meeting_with_different_heights() {
+        assert_eq!(
+            verify_code(
+                "
+                push bool true
+                jump_if_true end
+                push int 1
+            end:
+                nop
+                "
+            ),
+            Err(vec![VerifyError::InconsistentStack {
+                address: 4,
+                first: 0,
+                second: 1,
+            }])
+        );
+    }
+
+    #[test]
+    fn widens_loops_that_grow_the_stack() {
+        // a call in statement position may leave a value behind on every iteration
+        assert_eq!(
+            verify_code(
+                "
+                jump start
+            function:
+                push int 1
+                ret
+            start:
+                call function
+                push bool true
+                jump_if_true start
+                "
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn checks_pops_after_a_loop_widened_the_height() {
+        // the call may return nothing, so `add` can find an empty stack
+        assert_eq!(
+            verify_code(
+                "
+                jump start
+            function:
+                ret
+            start:
+                call function
+                push bool true
+                jump_if_true out
+                jump start
+            out:
+                add
+                "
+            ),
+            Err(vec![VerifyError::StackUnderflow {
+                address: 7,
+                needed: 2,
+                available: 0,
+            }])
+        );
+    }
+
+    #[test]
+    fn loops_that_push_a_known_amount_are_inconsistent() {
+        assert_eq!(
+            verify_code(
+                "
+            again:
+                push int 1
+                push bool true
+                jump_if_true again
+                "
+            ),
+            Err(vec![VerifyError::InconsistentStack {
+                address: 1,
+                first: 0,
+                second: 1,
+            }])
+        );
+    }
+
+    #[test]
+    fn return_needs_a_function() {
+        assert_eq!(
+            verify_code("    ret"),
+            Err(vec![VerifyError::ReturnOutsideFunction { address: 1 }])
+        );
+    }
+}
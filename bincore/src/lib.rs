@@ -1,2 +1,4 @@
+pub mod assembly;
 pub mod data;
 pub mod executable;
+pub mod stdlib;
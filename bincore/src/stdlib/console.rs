@@ -0,0 +1,164 @@
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
+use crate::executable::native::{NativeModule, Variadic};
+use crate::executable::runtime::Runtime;
+
+/// Adds the output builtins to `module`
+pub fn register(module: NativeModule) -> NativeModule {
+    module
+        .function(
+            "ipakita",
+            |runtime: &mut Runtime, values: Variadic<Value>| {
+                let line = values_into_printable(runtime, values.0)?;
+                writeln!(runtime.stdout, "{}", line).map_err(RuntimeError::from)
+            },
+        )
+        .function(
+            "isulat",
+            |runtime: &mut Runtime, values: Variadic<Value>| {
+                let text = values_into_printable(runtime, values.0)?;
+                write!(runtime.stdout, "{}", text)?;
+                runtime.stdout.flush().map_err(RuntimeError::from)
+            },
+        )
+}
+
+fn values_into_printable(runtime: &Runtime, values: Vec<Value>) -> Result<String, RuntimeError> {
+    let values = values
+        .into_iter()
+        .map(|value| value_into_printable(value, runtime))
+        .collect::<Result<Vec<String>, RuntimeError>>()?;
+
+    Ok(values.join(" "))
+}
+
+/// `value` as `ipakita` shows it, a list or object met again inside itself shows as `[...]` or
+/// `Name { ... }`
+pub fn value_into_printable(value: Value, runtime: &Runtime) -> Result<String, RuntimeError> {
+    printable(value, runtime, &mut vec![])
+}
+
+/// `path` holds the lists and objects currently being shown
+fn printable(
+    value: Value,
+    runtime: &Runtime,
+    path: &mut Vec<Value>,
+) -> Result<String, RuntimeError> {
+    let text = match value {
+        Value::Int(value) => value.to_string(),
+        Value::Float(value) => value.to_string(),
+        Value::StrRef(_) => runtime.from_value::<String>(value)?,
+        Value::Bool(value) => value.to_string(),
+        Value::ListRef(id) => {
+            let list = match runtime.lists.get(&id) {
+                Some(list) => list,
+                None => return Err(RuntimeError::InvalidReference(value)),
+            };
+            if path.contains(&value) {
+                return Ok("[...]".to_string());
+            }
+            path.push(value);
+
+            let mut stringed = vec![];
+            for value in list {
+                stringed.push(printable(*value, runtime, path)?);
+            }
+            path.pop();
+
+            format!("[{}]", stringed.join(", "))
+        }
+        Value::ObjectRef(id) => {
+            let object = match runtime.objects.get(&id) {
+                Some(object) => object,
+                None => return Err(RuntimeError::InvalidReference(value)),
+            };
+            let descriptor = &object.descriptor;
+            if path.contains(&value) {
+                return Ok(format!("{} {{ ... }}", descriptor.name));
+            }
+            path.push(value);
+
+            let mut string = vec![];
+            for (name, value) in descriptor
+                .members_by_index
+                .iter()
+                .zip(object.members.iter())
+            {
+                string.push(format!("{}: {}", name, printable(*value, runtime, path)?));
+            }
+            path.pop();
+
+            format!("{} {{ {} }}", descriptor.name, string.join(", "))
+        }
+        Value::Char(value) => value.to_string(),
+        Value::Null => "wala".to_string(),
+    };
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::object::ObjectDescriptor;
+    use crate::executable::output::SharedBuffer;
+    use crate::stdlib::call;
+
+    #[test]
+    fn shows_nested_values() {
+        let mut runtime = Runtime::new();
+        let name = runtime.new_string("Ana".to_string());
+        let list = runtime.new_list(vec![Value::Int(1), Value::Float(2.5), Value::Null]);
+        let descriptor = ObjectDescriptor::new("Tawo", vec!["ngalan".to_string(), "x".to_string()]);
+        let object = runtime.new_object(descriptor, vec![name, list]);
+
+        assert_eq!(
+            value_into_printable(object, &runtime).unwrap(),
+            "Tawo { ngalan: Ana, x: [1, 2.5, wala] }"
+        );
+    }
+
+    #[test]
+    fn shows_values_inside_themselves_once() {
+        let mut runtime = Runtime::new();
+        let inner = runtime.new_list(vec![]);
+        let list = runtime.new_list(vec![inner, inner]);
+        // the same list twice is not a cycle
+        assert_eq!(value_into_printable(list, &runtime).unwrap(), "[[], []]");
+
+        runtime.lists.get_mut(&0).unwrap().push(list);
+        assert_eq!(
+            value_into_printable(list, &runtime).unwrap(),
+            "[[[...]], [[...]]]"
+        );
+
+        let descriptor = ObjectDescriptor::new("Node", vec!["next".to_string()]);
+        let object = runtime.new_object(descriptor, vec![Value::Null]);
+        runtime.objects.get_mut(&0).unwrap().members[0] = object;
+        assert_eq!(
+            value_into_printable(object, &runtime).unwrap(),
+            "Node { next: Node { ... } }"
+        );
+    }
+
+    #[test]
+    fn invalid_references_are_errors() {
+        let mut runtime = Runtime::new();
+        for value in [Value::StrRef(7), Value::ListRef(7), Value::ObjectRef(7)] {
+            assert_eq!(
+                value_into_printable(value, &runtime),
+                Err(RuntimeError::InvalidReference(value))
+            );
+        }
+
+        let output = SharedBuffer::new();
+        runtime.register_module(register(NativeModule::new("")));
+        runtime.set_stdout(output.clone());
+        let list = runtime.new_list(vec![Value::ListRef(7)]);
+        assert_eq!(
+            call(&mut runtime, "ipakita", vec![Value::Int(1), list]),
+            Err(RuntimeError::InvalidReference(Value::ListRef(7)))
+        );
+        assert_eq!(output.contents(), "");
+    }
+}
@@ -0,0 +1,69 @@
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+use std::io::Write;
+use std::path::PathBuf;
+
+pub const NAME: &str = "file";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("read", 1),
+    ExternDescriptor::fixed("write", 2),
+    ExternDescriptor::fixed("append", 2),
+    ExternDescriptor::fixed("exists", 1),
+    ExternDescriptor::fixed("list", 1),
+];
+
+/// Whole file operations, each call opens and closes the file. There is no `open`, a value has
+/// no way to hold on to an open file. Paths outside of the runtime's `Capabilities::file_roots`
+/// are rejected.
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("read", |runtime: &mut Runtime, path: String| {
+            let path = allowed_path(runtime, &path)?;
+            Ok(std::fs::read_to_string(path)?)
+        })
+        .function(
+            "write",
+            |runtime: &mut Runtime, path: String, text: String| {
+                let path = allowed_path(runtime, &path)?;
+                Ok(std::fs::write(path, text)?)
+            },
+        )
+        .function(
+            "append",
+            |runtime: &mut Runtime, path: String, text: String| {
+                let path = allowed_path(runtime, &path)?;
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)?;
+                Ok(file.write_all(text.as_bytes())?)
+            },
+        )
+        .function("exists", |runtime: &mut Runtime, path: String| {
+            Ok(allowed_path(runtime, &path)?.exists())
+        })
+        .function("list", |runtime: &mut Runtime, path: String| {
+            let path = allowed_path(runtime, &path)?;
+
+            let mut names = vec![];
+            for entry in std::fs::read_dir(path)? {
+                names.push(entry?.file_name().to_string_lossy().to_string());
+            }
+            names.sort();
+
+            Ok(names)
+        })
+}
+
+fn allowed_path(runtime: &Runtime, path: &str) -> Result<PathBuf, RuntimeError> {
+    match runtime.capabilities.check_path(path) {
+        Some(path) => Ok(path),
+        None => Err(RuntimeError::PermissionDenied(format!(
+            "file access to '{}'",
+            path
+        ))),
+    }
+}
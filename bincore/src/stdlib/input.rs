@@ -0,0 +1,133 @@
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+
+pub const NAME: &str = "input";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("line", 1),
+    ExternDescriptor::fixed("int", 1),
+    ExternDescriptor::fixed("float", 1),
+];
+
+/// Each function writes its prompt to the runtime's stdout and reads one line from its stdin
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("line", read_line)
+        .function("int", |runtime: &mut Runtime, prompt: String| {
+            let line = read_line(runtime, prompt)?;
+            match line.trim().parse::<i64>() {
+                Ok(value) => Ok(value),
+                Err(_) => Err(RuntimeError::Native(format!(
+                    "'{}' is not a valid int",
+                    line
+                ))),
+            }
+        })
+        .function("float", |runtime: &mut Runtime, prompt: String| {
+            let line = read_line(runtime, prompt)?;
+            match line.trim().parse::<f64>() {
+                Ok(value) => Ok(value),
+                Err(_) => Err(RuntimeError::Native(format!(
+                    "'{}' is not a valid float",
+                    line
+                ))),
+            }
+        })
+}
+
+fn read_line(runtime: &mut Runtime, prompt: String) -> Result<String, RuntimeError> {
+    write!(runtime.stdout, "{}", prompt)?;
+    runtime.stdout.flush()?;
+
+    let mut line = String::new();
+    if runtime.stdin.read_line(&mut line)? == 0 {
+        return Err(RuntimeError::Io("Reached the end of the input".to_string()));
+    }
+
+    let length = line.trim_end_matches(['\r', '\n']).len();
+    line.truncate(length);
+
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::value::Value;
+    use crate::executable::output::SharedBuffer;
+    use crate::stdlib::call;
+    use std::io::Cursor;
+
+    fn runtime(input: &str) -> (Runtime, SharedBuffer) {
+        let output = SharedBuffer::new();
+        let mut runtime = Runtime::new();
+        runtime.register_module(module());
+        runtime.set_stdin(Cursor::new(input.as_bytes().to_vec()));
+        runtime.set_stdout(output.clone());
+        (runtime, output)
+    }
+
+    fn prompt(runtime: &mut Runtime, text: &str) -> Value {
+        runtime.new_string(text.to_string())
+    }
+
+    #[test]
+    fn writes_the_prompt_and_reads_lines_without_the_newline() {
+        let (mut runtime, output) = runtime("Maria\r\n  kumusta \n");
+
+        let name = prompt(&mut runtime, "Ngalan: ");
+        let line = call(&mut runtime, "input.line", vec![name])
+            .unwrap()
+            .unwrap();
+        assert_eq!(runtime.from_value::<String>(line).unwrap(), "Maria");
+        assert_eq!(output.contents(), "Ngalan: ");
+
+        let empty = prompt(&mut runtime, "");
+        let line = call(&mut runtime, "input.line", vec![empty])
+            .unwrap()
+            .unwrap();
+        assert_eq!(runtime.from_value::<String>(line).unwrap(), "  kumusta ");
+    }
+
+    #[test]
+    fn the_last_line_does_not_need_a_newline() {
+        let (mut runtime, _) = runtime("42");
+        let empty = prompt(&mut runtime, "");
+        assert_eq!(
+            call(&mut runtime, "input.int", vec![empty]),
+            Ok(Some(Value::Int(42)))
+        );
+    }
+
+    #[test]
+    fn end_of_input_is_an_error() {
+        let (mut runtime, output) = runtime("");
+        let question = prompt(&mut runtime, "? ");
+        assert_eq!(
+            call(&mut runtime, "input.line", vec![question]),
+            Err(RuntimeError::Io("Reached the end of the input".to_string()))
+        );
+        assert_eq!(output.contents(), "? ");
+    }
+
+    #[test]
+    fn numbers_are_parsed_after_trimming() {
+        let (mut runtime, _) = runtime(" 7 \n2.5\nusa\n");
+        let empty = prompt(&mut runtime, "");
+
+        assert_eq!(
+            call(&mut runtime, "input.int", vec![empty]),
+            Ok(Some(Value::Int(7)))
+        );
+        assert_eq!(
+            call(&mut runtime, "input.float", vec![empty]),
+            Ok(Some(Value::Float(2.5)))
+        );
+        assert_eq!(
+            call(&mut runtime, "input.int", vec![empty]),
+            Err(RuntimeError::Native("'usa' is not a valid int".to_string()))
+        );
+    }
+}
@@ -0,0 +1,700 @@
+use crate::data::object::ObjectDescriptor;
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+
+pub const NAME: &str = "json";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("parse", 1),
+    ExternDescriptor::fixed("stringify", 1),
+    ExternDescriptor::fixed("get", 2),
+    ExternDescriptor::fixed("set", 3),
+    ExternDescriptor::fixed("keys", 1),
+];
+
+/// Name of the descriptor given to objects created from JSON
+pub const OBJECT_NAME: &str = "json";
+
+/// Nesting limit of parsed documents, deeper input is rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 512;
+
+/// JSON arrays become lists, objects become objects with a descriptor built from their keys and
+/// `null` becomes `wala`. `get`, `set` and `keys` work on any object by member name.
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("parse", |runtime: &mut Runtime, text: String| {
+            parse(runtime, &text)
+        })
+        .function("stringify", |runtime: &mut Runtime, value: Value| {
+            stringify(runtime, value)
+        })
+        .function(
+            "get",
+            |runtime: &mut Runtime, object: Value, key: String| {
+                let object = &runtime.objects[&object_id(runtime, object)?];
+
+                Ok::<Option<Value>, RuntimeError>(
+                    object
+                        .descriptor
+                        .members
+                        .get(&key)
+                        .map(|index| object.members[*index]),
+                )
+            },
+        )
+        .function(
+            "set",
+            |runtime: &mut Runtime, object: Value, key: String, value: Value| {
+                let object_id = object_id(runtime, object)?;
+                set_member(runtime, object_id, key, value);
+                Ok(())
+            },
+        )
+        .function("keys", |runtime: &mut Runtime, object: Value| {
+            let object = &runtime.objects[&object_id(runtime, object)?];
+            Ok::<Vec<String>, RuntimeError>(object.descriptor.members_by_index.clone())
+        })
+}
+
+fn object_id(runtime: &Runtime, value: Value) -> Result<usize, RuntimeError> {
+    match value {
+        Value::ObjectRef(object) if runtime.objects.contains_key(&object) => Ok(object),
+        Value::ObjectRef(_) => Err(RuntimeError::InvalidReference(value)),
+        found => Err(RuntimeError::TypeMismatch {
+            expected: "object",
+            found,
+        }),
+    }
+}
+
+/// Sets a member by name, an unknown name gives the object a new descriptor with the member added
+fn set_member(runtime: &mut Runtime, object_id: usize, key: String, value: Value) {
+    let object = runtime.objects.get_mut(&object_id).unwrap();
+
+    if let Some(index) = object.descriptor.members.get(&key) {
+        object.members[*index] = value;
+        return;
+    }
+
+    let mut members_by_index = object.descriptor.members_by_index.clone();
+    members_by_index.push(key);
+
+    object.descriptor = ObjectDescriptor::new(&object.descriptor.name, members_by_index).into();
+    object.members.push(value);
+}
+
+pub fn parse(runtime: &mut Runtime, text: &str) -> Result<Value, RuntimeError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        position: 0,
+        runtime,
+    };
+
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.position < parser.chars.len() {
+        return Err(parser.error("unexpected input after the value"));
+    }
+
+    Ok(value)
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    position: usize,
+    runtime: &'a mut Runtime,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> RuntimeError {
+        RuntimeError::Native(format!(
+            "Invalid JSON at position {}: {}",
+            self.position, message
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek();
+        self.position += 1;
+        char
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), RuntimeError> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(char) if char == expected => Ok(()),
+            _ => {
+                self.position -= 1;
+                Err(self.error(&format!("expected '{}'", expected)))
+            }
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, RuntimeError> {
+        for expected in keyword.chars() {
+            if self.next() != Some(expected) {
+                self.position -= 1;
+                return Err(self.error(&format!("expected '{}'", keyword)));
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, RuntimeError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => {
+                let string = self.string()?;
+                Ok(self.runtime.new_string(string))
+            }
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('n') => self.keyword("null", Value::Null),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, RuntimeError> {
+        self.expect('[')?;
+
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(self.runtime.new_list(values));
+        }
+
+        loop {
+            values.push(self.value(depth + 1)?);
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => break,
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("expected ',' or ']'"));
+                }
+            }
+        }
+
+        Ok(self.runtime.new_list(values))
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, RuntimeError> {
+        self.expect('{')?;
+
+        let mut keys: Vec<String> = vec![];
+        let mut values = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(self
+                .runtime
+                .new_object(ObjectDescriptor::new(OBJECT_NAME, keys), values));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key = self.string()?;
+            self.expect(':')?;
+            let value = self.value(depth + 1)?;
+
+            // a repeated key keeps its first position and its last value
+            match keys.iter().position(|existing| *existing == key) {
+                Some(index) => values[index] = value,
+                None => {
+                    keys.push(key);
+                    values.push(value);
+                }
+            }
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("expected ',' or '}'"));
+                }
+            }
+        }
+
+        Ok(self
+            .runtime
+            .new_object(ObjectDescriptor::new(OBJECT_NAME, keys), values))
+    }
+
+    fn string(&mut self) -> Result<String, RuntimeError> {
+        self.expect('"')?;
+
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => match self.next() {
+                    Some('"') => string.push('"'),
+                    Some('\\') => string.push('\\'),
+                    Some('/') => string.push('/'),
+                    Some('b') => string.push('\u{8}'),
+                    Some('f') => string.push('\u{c}'),
+                    Some('n') => string.push('\n'),
+                    Some('r') => string.push('\r'),
+                    Some('t') => string.push('\t'),
+                    Some('u') => string.push(self.unicode_escape()?),
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.error("invalid escape"));
+                    }
+                },
+                Some(char) if char < ' ' => {
+                    self.position -= 1;
+                    return Err(self.error("control character in string"));
+                }
+                Some(char) => string.push(char),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, RuntimeError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.next().and_then(|char| char.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => {
+                    self.position -= 1;
+                    return Err(self.error("expected four hex digits"));
+                }
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, RuntimeError> {
+        let high = self.hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.next() != Some('\\') || self.next() != Some('u') {
+                return Err(self.error("expected a low surrogate"));
+            }
+
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("expected a low surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn number(&mut self) -> Result<Value, RuntimeError> {
+        let start = self.position;
+        let mut is_float = false;
+
+        while let Some(char) = self.peek() {
+            match char {
+                '0'..='9' | '-' | '+' => {}
+                '.' | 'e' | 'E' => is_float = true,
+                _ => break,
+            }
+            self.position += 1;
+        }
+
+        let text = self.chars[start..self.position].iter().collect::<String>();
+        if !is_number(&text) {
+            self.position = start;
+            return Err(self.error(&format!("invalid number '{}'", text)));
+        }
+
+        if !is_float {
+            if let Ok(value) = text.parse::<i64>() {
+                return Ok(Value::Int(value));
+            }
+        }
+
+        match text.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(Value::Float(value)),
+            _ => {
+                self.position = start;
+                Err(self.error(&format!("invalid number '{}'", text)))
+            }
+        }
+    }
+}
+
+/// Whether `text` follows RFC 8259's number grammar, which Rust's parsers are more lenient than:
+/// no leading zeros, no '+' sign and digits on both sides of the '.'
+fn is_number(text: &str) -> bool {
+    let digits = |text: &str| {
+        text.len()
+            - text
+                .trim_start_matches(|char: char| char.is_ascii_digit())
+                .len()
+    };
+
+    let mut rest = text.strip_prefix('-').unwrap_or(text);
+    let integer = digits(rest);
+    if integer == 0 || (integer > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    rest = &rest[integer..];
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let length = digits(fraction);
+        if length == 0 {
+            return false;
+        }
+        rest = &fraction[length..];
+    }
+
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let length = digits(exponent);
+        if length == 0 {
+            return false;
+        }
+        rest = &exponent[length..];
+    }
+
+    rest.is_empty()
+}
+
+pub fn stringify(runtime: &Runtime, value: Value) -> Result<String, RuntimeError> {
+    let mut json = String::new();
+    write_value(runtime, value, &mut vec![], &mut json)?;
+    Ok(json)
+}
+
+/// `path` holds the lists and objects currently being written, meeting one again means a cycle and
+/// nesting deeper than `MAX_DEPTH` is refused like `parse` does
+fn write_value(
+    runtime: &Runtime,
+    value: Value,
+    path: &mut Vec<Value>,
+    json: &mut String,
+) -> Result<(), RuntimeError> {
+    match value {
+        Value::Int(value) => json.push_str(&value.to_string()),
+        Value::Float(value) if value.is_finite() => json.push_str(&format!("{:?}", value)),
+        Value::Float(value) => {
+            return Err(RuntimeError::Native(format!(
+                "Cannot write {} as JSON",
+                value
+            )))
+        }
+        Value::Char(char) => write_string(&char.to_string(), json),
+        Value::Bool(value) => json.push_str(&value.to_string()),
+        Value::Null => json.push_str("null"),
+        Value::StrRef(_) => write_string(&runtime.from_value::<String>(value)?, json),
+        Value::ListRef(list) => {
+            let values = match runtime.lists.get(&list) {
+                Some(values) => values,
+                None => return Err(RuntimeError::InvalidReference(value)),
+            };
+            enter(path, value)?;
+
+            json.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+                write_value(runtime, *value, path, json)?;
+            }
+            json.push(']');
+
+            path.pop();
+        }
+        Value::ObjectRef(object) => {
+            let object = match runtime.objects.get(&object) {
+                Some(object) => object,
+                None => return Err(RuntimeError::InvalidReference(value)),
+            };
+            enter(path, value)?;
+
+            json.push('{');
+            for (index, name) in object.descriptor.members_by_index.iter().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+                write_string(name, json);
+                json.push(':');
+                write_value(runtime, object.members[index], path, json)?;
+            }
+            json.push('}');
+
+            path.pop();
+        }
+    }
+
+    Ok(())
+}
+
+fn enter(path: &mut Vec<Value>, value: Value) -> Result<(), RuntimeError> {
+    if path.contains(&value) {
+        return Err(RuntimeError::Native(
+            "Cannot write a value that contains itself as JSON".to_string(),
+        ));
+    }
+
+    if path.len() > MAX_DEPTH {
+        return Err(RuntimeError::Native(
+            "Cannot write a value nested this deeply as JSON".to_string(),
+        ));
+    }
+
+    path.push(value);
+    Ok(())
+}
+
+fn write_string(string: &str, json: &mut String) {
+    json.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '\u{8}' => json.push_str("\\b"),
+            '\u{c}' => json.push_str("\\f"),
+            char if char < ' ' => json.push_str(&format!("\\u{:04x}", char as u32)),
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(text: &str) -> String {
+        let mut runtime = Runtime::new();
+        let value = parse(&mut runtime, text).unwrap();
+        stringify(&runtime, value).unwrap()
+    }
+
+    fn parse_error(text: &str) -> String {
+        match parse(&mut Runtime::new(), text) {
+            Err(RuntimeError::Native(message)) => message,
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn round_trips_nested_values() {
+        assert_eq!(
+            round_trip(r#" { "a" : [1, -2.5, true, null, {"b": []}], "c": {} , "d": "x"} "#),
+            r#"{"a":[1,-2.5,true,null,{"b":[]}],"c":{},"d":"x"}"#
+        );
+        assert_eq!(round_trip("1e2"), "100.0");
+        assert_eq!(round_trip("[[[[]]]]"), "[[[[]]]]");
+    }
+
+    #[test]
+    fn repeated_keys_keep_the_first_position_and_the_last_value() {
+        assert_eq!(
+            round_trip(r#"{"a": 1, "b": 2, "a": 3}"#),
+            r#"{"a":3,"b":2}"#
+        );
+    }
+
+    #[test]
+    fn reads_escapes() {
+        let mut runtime = Runtime::new();
+        let value = parse(&mut runtime, r#""\"\\\/\b\f\n\r\t\u00e9\ud83d\ude00""#).unwrap();
+
+        assert_eq!(
+            runtime.from_value::<String>(value).unwrap(),
+            "\"\\/\u{8}\u{c}\n\r\té\u{1F600}"
+        );
+    }
+
+    #[test]
+    fn writes_escapes() {
+        let mut runtime = Runtime::new();
+        let value = runtime.new_string("\"\\/\u{8}\u{c}\n\r\t\u{1}é\u{1F600}".to_string());
+
+        assert_eq!(
+            stringify(&runtime, value).unwrap(),
+            r#""\"\\/\b\f\n\r\t\u0001é😀""#
+        );
+        assert_eq!(stringify(&runtime, Value::Char('"')).unwrap(), r#""\"""#);
+    }
+
+    #[test]
+    fn reports_where_parsing_stopped() {
+        assert_eq!(
+            parse_error("[1, 2"),
+            "Invalid JSON at position 5: expected ',' or ']'"
+        );
+        assert_eq!(
+            parse_error("{\"a\" 1}"),
+            "Invalid JSON at position 5: expected ':'"
+        );
+        assert_eq!(
+            parse_error("{1: 2}"),
+            "Invalid JSON at position 1: expected a string key"
+        );
+        assert_eq!(
+            parse_error("nul"),
+            "Invalid JSON at position 3: expected 'null'"
+        );
+        assert_eq!(
+            parse_error("1 2"),
+            "Invalid JSON at position 2: unexpected input after the value"
+        );
+        assert_eq!(
+            parse_error("\"abc"),
+            "Invalid JSON at position 5: unterminated string"
+        );
+        assert_eq!(
+            parse_error("\"\\x\""),
+            "Invalid JSON at position 2: invalid escape"
+        );
+        assert_eq!(
+            parse_error("\"\\ud83d\""),
+            "Invalid JSON at position 8: expected a low surrogate"
+        );
+        assert_eq!(
+            parse_error("\"a\nb\""),
+            "Invalid JSON at position 2: control character in string"
+        );
+        assert_eq!(
+            parse_error("1-2"),
+            "Invalid JSON at position 0: invalid number '1-2'"
+        );
+        assert_eq!(
+            parse_error(""),
+            "Invalid JSON at position 0: unexpected end of input"
+        );
+    }
+
+    #[test]
+    fn follows_the_number_grammar() {
+        assert_eq!(
+            round_trip("[0, -0, 10, 0.5, -1.25e-3, 2E+2]"),
+            "[0,0,10,0.5,-0.00125,200.0]"
+        );
+
+        for (text, number) in [
+            ("01", "01"),
+            ("-01", "-01"),
+            ("00.5", "00.5"),
+            ("1.", "1."),
+            ("[1.]", "1."),
+            (".5", ""),
+            ("-", "-"),
+            ("1.e3", "1.e3"),
+            ("1e", "1e"),
+            ("+1", ""),
+        ] {
+            let message = parse_error(text);
+            if number.is_empty() {
+                assert!(
+                    message.ends_with("expected a value"),
+                    "{}: {}",
+                    text,
+                    message
+                );
+            } else {
+                assert!(
+                    message.ends_with(&format!("invalid number '{}'", number)),
+                    "{}: {}",
+                    text,
+                    message
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = "[".repeat(MAX_DEPTH + 1) + &"]".repeat(MAX_DEPTH + 1);
+        assert_eq!(round_trip(&nested), nested);
+
+        let too_deep = "[".repeat(MAX_DEPTH + 2) + &"]".repeat(MAX_DEPTH + 2);
+        assert!(parse_error(&too_deep).ends_with("nested too deeply"));
+
+        let mut runtime = Runtime::new();
+        let mut list = runtime.new_list(vec![]);
+        for _ in 0..MAX_DEPTH {
+            list = runtime.new_list(vec![list]);
+        }
+        assert_eq!(stringify(&runtime, list).unwrap(), nested);
+
+        let list = runtime.new_list(vec![list]);
+        assert_eq!(
+            stringify(&runtime, list),
+            Err(RuntimeError::Native(
+                "Cannot write a value nested this deeply as JSON".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn refuses_values_json_cannot_hold() {
+        let mut runtime = Runtime::new();
+        let list = runtime.new_list(vec![]);
+        let outer = runtime.new_list(vec![list, list]);
+        // the same list twice is fine, only a list inside itself is a cycle
+        assert_eq!(stringify(&runtime, outer).unwrap(), "[[],[]]");
+
+        runtime.lists.get_mut(&0).unwrap().push(outer);
+        assert!(stringify(&runtime, outer).is_err());
+        assert!(stringify(&runtime, Value::Float(f64::NAN)).is_err());
+        assert_eq!(
+            stringify(&runtime, Value::ListRef(9)),
+            Err(RuntimeError::InvalidReference(Value::ListRef(9)))
+        );
+    }
+
+    #[test]
+    fn set_adds_unknown_members() {
+        let mut runtime = Runtime::new();
+        let object = parse(&mut runtime, r#"{"a": 1}"#).unwrap();
+        let id = object_id(&runtime, object).unwrap();
+
+        set_member(&mut runtime, id, "a".to_string(), Value::Int(2));
+        set_member(&mut runtime, id, "b".to_string(), Value::Bool(true));
+
+        assert_eq!(stringify(&runtime, object).unwrap(), r#"{"a":2,"b":true}"#);
+        assert_eq!(
+            object_id(&runtime, Value::Int(1)),
+            Err(RuntimeError::TypeMismatch {
+                expected: "object",
+                found: Value::Int(1),
+            })
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use crate::data::convert::ListRef;
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
+use crate::executable::native::{NativeModule, Variadic};
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+
+pub const NAME: &str = "lista";
+
+/// The list functions programs called before there were modules, also exposed unqualified
+pub const BUILTINS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("butngan", 2),
+    ExternDescriptor::fixed("kuhaan", 1),
+    ExternDescriptor::fixed("bag_ong_lista", 0),
+    ExternDescriptor::variadic("bag_ong_list_nga_naay_sulod", 0),
+    ExternDescriptor::fixed("index_set", 3),
+    ExternDescriptor::fixed("indeks_kuha", 2),
+    ExternDescriptor::fixed("katas_on", 1),
+];
+
+/// Functions only reachable as `lista.<name>`, the manifest adds `BUILTINS` to them
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("insert", 3),
+    ExternDescriptor::fixed("remove", 2),
+    ExternDescriptor::fixed("slice", 3),
+    ExternDescriptor::fixed("reverse", 1),
+    ExternDescriptor::fixed("sort", 1),
+    ExternDescriptor::fixed("contains", 2),
+    ExternDescriptor::fixed("index_of", 2),
+    ExternDescriptor::fixed("concat", 2),
+];
+
+/// `BUILTINS`, reachable with and without `lista.` in front, plus `EXTERNS`
+///
+/// `insert`, `remove`, `reverse` and `sort` change the list in place, `slice` and `concat` return a
+/// new list. `contains` and `index_of` compare like `==` does. `sort` only orders numbers or
+/// strings, sorting with a comparator, `map` and `filter` have to wait until functions can be
+/// passed as values.
+pub fn module() -> NativeModule {
+    let module = NativeModule::new(NAME)
+        .function("butngan", butngan)
+        .function("kuhaan", kuhaan)
+        .function("bag_ong_lista", bag_ong_lista)
+        .function("bag_ong_list_nga_naay_sulod", bag_ong_list_nga_naay_sulod)
+        .function("index_set", index_set)
+        .function("indeks_kuha", indeks_kuha)
+        .function("katas_on", katas_on);
+
+    BUILTINS
+        .iter()
+        .fold(module, |module, descriptor| module.builtin(descriptor.name))
+        .function("insert", insert)
+        .function("remove", remove)
+        .function("slice", slice)
+        .function("reverse", |runtime: &mut Runtime, list: ListRef| {
+            runtime.lists.get_mut(&list.0).unwrap().reverse();
+        })
+        .function("sort", sort)
+        .function(
+            "contains",
+            |runtime: &mut Runtime, list: ListRef, value: Value| {
+                Ok::<bool, RuntimeError>(index_of(runtime, list, value)? >= 0)
+            },
+        )
+        .function("index_of", index_of)
+        .function(
+            "concat",
+            |runtime: &mut Runtime, left: ListRef, right: ListRef| {
+                let mut list = runtime.lists[&left.0].clone();
+                list.extend_from_slice(&runtime.lists[&right.0]);
+                runtime.new_list(list)
+            },
+        )
+}
+
+pub fn list_index(index: i64, list: &[Value]) -> Result<usize, RuntimeError> {
+    if index < 0 || index as usize >= list.len() {
+        return Err(RuntimeError::IndexOutOfBounds {
+            index,
+            length: list.len(),
+        });
+    }
+
+    Ok(index as usize)
+}
+
+fn butngan(runtime: &mut Runtime, list: ListRef, value: Value) {
+    runtime.lists.get_mut(&list.0).unwrap().push(value);
+}
+
+fn kuhaan(runtime: &mut Runtime, list: ListRef) -> Result<Value, RuntimeError> {
+    match runtime.lists.get_mut(&list.0).unwrap().pop() {
+        Some(value) => Ok(value),
+        None => Err(RuntimeError::Native(
+            "Cannot take a value from an empty list".to_string(),
+        )),
+    }
+}
+
+fn bag_ong_lista(runtime: &mut Runtime) -> Value {
+    runtime.new_list(Vec::new())
+}
+
+fn bag_ong_list_nga_naay_sulod(runtime: &mut Runtime, values: Variadic<Value>) -> Value {
+    runtime.new_list(values.0)
+}
+
+fn index_set(
+    runtime: &mut Runtime,
+    list: ListRef,
+    index: i64,
+    value: Value,
+) -> Result<(), RuntimeError> {
+    let list = runtime.lists.get_mut(&list.0).unwrap();
+    let index = list_index(index, list)?;
+
+    list[index] = value;
+    Ok(())
+}
+
+fn indeks_kuha(runtime: &mut Runtime, list: Value, index: i64) -> Result<Value, RuntimeError> {
+    match list {
+        Value::ListRef(_) => {
+            let ListRef(list) = runtime.from_value(list)?;
+            let list = &runtime.lists[&list];
+
+            Ok(list[list_index(index, list)?])
+        }
+        Value::StrRef(_) => {
+            let string = runtime.from_value::<String>(list)?;
+            let length = string.chars().count();
+
+            match string.chars().nth(index as usize) {
+                Some(char) if index >= 0 => Ok(Value::Char(char)),
+                _ => Err(RuntimeError::IndexOutOfBounds { index, length }),
+            }
+        }
+        found => Err(RuntimeError::TypeMismatch {
+            expected: "list or string",
+            found,
+        }),
+    }
+}
+
+fn katas_on(runtime: &mut Runtime, value: Value) -> Result<i64, RuntimeError> {
+    match value {
+        Value::ListRef(_) => {
+            let ListRef(list) = runtime.from_value(value)?;
+            Ok(runtime.lists[&list].len() as i64)
+        }
+        Value::StrRef(_) => Ok(runtime.from_value::<String>(value)?.chars().count() as i64),
+        found => Err(RuntimeError::TypeMismatch {
+            expected: "list or string",
+            found,
+        }),
+    }
+}
+
+fn insert(
+    runtime: &mut Runtime,
+    list: ListRef,
+    index: i64,
+    value: Value,
+) -> Result<(), RuntimeError> {
+    let list = runtime.lists.get_mut(&list.0).unwrap();
+
+    // inserting right after the last value appends
+    if index < 0 || index as usize > list.len() {
+        return Err(RuntimeError::IndexOutOfBounds {
+            index,
+            length: list.len(),
+        });
+    }
+
+    list.insert(index as usize, value);
+    Ok(())
+}
+
+fn remove(runtime: &mut Runtime, list: ListRef, index: i64) -> Result<Value, RuntimeError> {
+    let list = runtime.lists.get_mut(&list.0).unwrap();
+    let index = list_index(index, list)?;
+
+    Ok(list.remove(index))
+}
+
+fn slice(
+    runtime: &mut Runtime,
+    list: ListRef,
+    start: i64,
+    end: i64,
+) -> Result<Value, RuntimeError> {
+    let values = &runtime.lists[&list.0];
+    let length = values.len();
+
+    for index in [start, end] {
+        if index < 0 || index as usize > length {
+            return Err(RuntimeError::IndexOutOfBounds { index, length });
+        }
+    }
+
+    if start > end {
+        return Err(RuntimeError::Native(format!(
+            "lista.slice start {} is after end {}",
+            start, end
+        )));
+    }
+
+    let values = values[start as usize..end as usize].to_vec();
+    Ok(runtime.new_list(values))
+}
+
+fn sort(runtime: &mut Runtime, list: ListRef) -> Result<(), RuntimeError> {
+    let values = &runtime.lists[&list.0];
+
+    // every value has to sort like the first one
+    let kind = values.first().and_then(|value| sort_kind(*value));
+    if let Some(found) = values
+        .iter()
+        .find(|value| kind.is_none() || sort_kind(**value) != kind)
+    {
+        return Err(RuntimeError::TypeMismatch {
+            expected: "list of numbers or strings",
+            found: *found,
+        });
+    }
+
+    if kind == Some("string") {
+        let mut keyed = values
+            .iter()
+            .map(|value| Ok((runtime.from_value::<String>(*value)?, *value)))
+            .collect::<Result<Vec<(String, Value)>, RuntimeError>>()?;
+        keyed.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        let list = runtime.lists.get_mut(&list.0).unwrap();
+        *list = keyed.into_iter().map(|(_, value)| value).collect();
+    } else {
+        let list = runtime.lists.get_mut(&list.0).unwrap();
+        list.sort_by(|left, right| match (left, right) {
+            (Value::Int(left), Value::Int(right)) => left.cmp(right),
+            _ => number(*left).total_cmp(&number(*right)),
+        });
+    }
+
+    Ok(())
+}
+
+fn sort_kind(value: Value) -> Option<&'static str> {
+    match value {
+        Value::Int(_) | Value::Float(_) => Some("number"),
+        Value::StrRef(_) | Value::Char(_) => Some("string"),
+        _ => None,
+    }
+}
+
+fn number(value: Value) -> f64 {
+    match value {
+        Value::Int(value) => value as f64,
+        Value::Float(value) => value,
+        _ => f64::NAN,
+    }
+}
+
+fn index_of(runtime: &mut Runtime, list: ListRef, value: Value) -> Result<i64, RuntimeError> {
+    for (index, item) in runtime.lists[&list.0].iter().enumerate() {
+        if runtime.values_equal(*item, value)? {
+            return Ok(index as i64);
+        }
+    }
+
+    Ok(-1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call;
+
+    fn runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_module(module());
+        runtime
+    }
+
+    fn values(runtime: &Runtime, list: Value) -> Vec<Value> {
+        let ListRef(list) = runtime.from_value(list).unwrap();
+        runtime.lists[&list].clone()
+    }
+
+    fn ints(values: &[i64]) -> Vec<Value> {
+        values.iter().map(|value| Value::Int(*value)).collect()
+    }
+
+    #[test]
+    fn inserts_anywhere_up_to_the_end() {
+        let mut runtime = runtime();
+        let list = runtime.new_list(ints(&[1, 2]));
+
+        call(
+            &mut runtime,
+            "lista.insert",
+            vec![list, Value::Int(2), Value::Int(3)],
+        )
+        .unwrap();
+        call(
+            &mut runtime,
+            "lista.insert",
+            vec![list, Value::Int(0), Value::Int(0)],
+        )
+        .unwrap();
+        assert_eq!(values(&runtime, list), ints(&[0, 1, 2, 3]));
+
+        assert_eq!(
+            call(
+                &mut runtime,
+                "lista.insert",
+                vec![list, Value::Int(5), Value::Int(9)]
+            ),
+            Err(RuntimeError::IndexOutOfBounds {
+                index: 5,
+                length: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn removes_and_reverses_in_place() {
+        let mut runtime = runtime();
+        let list = runtime.new_list(ints(&[1, 2, 3]));
+
+        let removed = call(&mut runtime, "lista.remove", vec![list, Value::Int(1)]).unwrap();
+        assert_eq!(removed, Some(Value::Int(2)));
+        call(&mut runtime, "lista.reverse", vec![list]).unwrap();
+        assert_eq!(values(&runtime, list), ints(&[3, 1]));
+
+        assert!(call(&mut runtime, "lista.remove", vec![list, Value::Int(2)]).is_err());
+    }
+
+    #[test]
+    fn slices_between_bounds() {
+        let mut runtime = runtime();
+        let list = runtime.new_list(ints(&[1, 2, 3]));
+        let slice = |runtime: &mut Runtime, start: i64, end: i64| {
+            call(
+                runtime,
+                "lista.slice",
+                vec![list, Value::Int(start), Value::Int(end)],
+            )
+            .map(|slice| values(runtime, slice.unwrap()))
+        };
+
+        assert_eq!(slice(&mut runtime, 1, 3), Ok(ints(&[2, 3])));
+        assert_eq!(slice(&mut runtime, 0, 0), Ok(vec![]));
+        assert_eq!(slice(&mut runtime, 3, 3), Ok(vec![]));
+        assert_eq!(
+            slice(&mut runtime, 0, 4),
+            Err(RuntimeError::IndexOutOfBounds {
+                index: 4,
+                length: 3,
+            })
+        );
+        assert_eq!(
+            slice(&mut runtime, -1, 2),
+            Err(RuntimeError::IndexOutOfBounds {
+                index: -1,
+                length: 3,
+            })
+        );
+        assert!(matches!(
+            slice(&mut runtime, 2, 1),
+            Err(RuntimeError::Native(_))
+        ));
+        // the original list stays as it was
+        assert_eq!(values(&runtime, list), ints(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn sorts_ints_and_floats_together() {
+        let mut runtime = runtime();
+        let list = runtime.new_list(vec![
+            Value::Float(2.5),
+            Value::Int(3),
+            Value::Int(-1),
+            Value::Float(0.5),
+        ]);
+
+        call(&mut runtime, "lista.sort", vec![list]).unwrap();
+        assert_eq!(
+            values(&runtime, list),
+            vec![
+                Value::Int(-1),
+                Value::Float(0.5),
+                Value::Float(2.5),
+                Value::Int(3)
+            ]
+        );
+    }
+
+    #[test]
+    fn sorts_strings_by_content() {
+        let mut runtime = runtime();
+        let strings = ["saging", "abokado", "mangga"]
+            .iter()
+            .map(|string| runtime.new_string(string.to_string()))
+            .collect::<Vec<Value>>();
+        let list = runtime.new_list(strings);
+
+        call(&mut runtime, "lista.sort", vec![list]).unwrap();
+        let sorted = values(&runtime, list)
+            .into_iter()
+            .map(|value| runtime.from_value::<String>(value).unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(sorted, vec!["abokado", "mangga", "saging"]);
+    }
+
+    #[test]
+    fn refuses_to_sort_mixed_lists() {
+        let mut runtime = runtime();
+        let string = runtime.new_string("a".to_string());
+        let list = runtime.new_list(vec![Value::Int(1), string]);
+
+        assert_eq!(
+            call(&mut runtime, "lista.sort", vec![list]),
+            Err(RuntimeError::TypeMismatch {
+                expected: "list of numbers or strings",
+                found: string,
+            })
+        );
+        assert_eq!(values(&runtime, list), vec![Value::Int(1), string]);
+    }
+
+    #[test]
+    fn finds_values_like_equality_does() {
+        let mut runtime = runtime();
+        let string = runtime.new_string("a".to_string());
+        let list = runtime.new_list(vec![string, Value::Int(2)]);
+        let other = runtime.new_string("a".to_string());
+
+        assert_eq!(
+            call(&mut runtime, "lista.index_of", vec![list, other]),
+            Ok(Some(Value::Int(0)))
+        );
+        assert_eq!(
+            call(&mut runtime, "lista.index_of", vec![list, Value::Char('a')]),
+            Ok(Some(Value::Int(0)))
+        );
+        // a string and an int cannot be compared
+        assert!(matches!(
+            call(&mut runtime, "lista.contains", vec![list, Value::Int(3)]),
+            Err(RuntimeError::TypeMismatch { .. })
+        ));
+
+        let numbers = runtime.new_list(ints(&[1, 2]));
+        assert_eq!(
+            call(&mut runtime, "lista.contains", vec![numbers, Value::Int(2)]),
+            Ok(Some(Value::Bool(true)))
+        );
+        assert_eq!(
+            call(&mut runtime, "lista.index_of", vec![numbers, Value::Int(3)]),
+            Ok(Some(Value::Int(-1)))
+        );
+    }
+
+    #[test]
+    fn concat_makes_a_new_list() {
+        let mut runtime = runtime();
+        let left = runtime.new_list(ints(&[1]));
+        let right = runtime.new_list(ints(&[2, 3]));
+
+        let list = call(&mut runtime, "lista.concat", vec![left, right])
+            .unwrap()
+            .unwrap();
+        assert_eq!(values(&runtime, list), ints(&[1, 2, 3]));
+        assert_eq!(values(&runtime, left), ints(&[1]));
+    }
+}
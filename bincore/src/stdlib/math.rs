@@ -0,0 +1,277 @@
+use crate::data::convert::Number;
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+use std::cmp::Ordering;
+
+pub const NAME: &str = "math";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("pi", 0),
+    ExternDescriptor::fixed("e", 0),
+    ExternDescriptor::fixed("sqrt", 1),
+    ExternDescriptor::fixed("abs", 1),
+    ExternDescriptor::fixed("floor", 1),
+    ExternDescriptor::fixed("ceil", 1),
+    ExternDescriptor::fixed("round", 1),
+    ExternDescriptor::fixed("min", 2),
+    ExternDescriptor::fixed("max", 2),
+    ExternDescriptor::fixed("sin", 1),
+    ExternDescriptor::fixed("cos", 1),
+    ExternDescriptor::fixed("tan", 1),
+    ExternDescriptor::fixed("asin", 1),
+    ExternDescriptor::fixed("acos", 1),
+    ExternDescriptor::fixed("atan", 1),
+    ExternDescriptor::fixed("atan2", 2),
+    ExternDescriptor::fixed("exp", 1),
+    ExternDescriptor::fixed("log", 1),
+    ExternDescriptor::fixed("log10", 1),
+    ExternDescriptor::fixed("to_int", 1),
+    ExternDescriptor::fixed("to_float", 1),
+];
+
+/// Functions that only make sense for floats take either numeric type and return a float,
+/// `abs`, `min` and `max` keep ints as ints and rounding always returns an int
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("pi", |_: &mut Runtime| std::f64::consts::PI)
+        .function("e", |_: &mut Runtime| std::f64::consts::E)
+        .function("sqrt", sqrt)
+        .function("abs", abs)
+        .function("floor", |_: &mut Runtime, value: Number| {
+            round_with(value, f64::floor)
+        })
+        .function("ceil", |_: &mut Runtime, value: Number| {
+            round_with(value, f64::ceil)
+        })
+        .function("round", |_: &mut Runtime, value: Number| {
+            round_with(value, f64::round)
+        })
+        .function("min", |_: &mut Runtime, left: Number, right: Number| {
+            pick(left, right, Ordering::Less)
+        })
+        .function("max", |_: &mut Runtime, left: Number, right: Number| {
+            pick(left, right, Ordering::Greater)
+        })
+        .function("sin", |_: &mut Runtime, value: Number| {
+            value.as_float().sin()
+        })
+        .function("cos", |_: &mut Runtime, value: Number| {
+            value.as_float().cos()
+        })
+        .function("tan", |_: &mut Runtime, value: Number| {
+            value.as_float().tan()
+        })
+        .function("asin", |_: &mut Runtime, value: Number| {
+            value.as_float().asin()
+        })
+        .function("acos", |_: &mut Runtime, value: Number| {
+            value.as_float().acos()
+        })
+        .function("atan", |_: &mut Runtime, value: Number| {
+            value.as_float().atan()
+        })
+        .function("atan2", |_: &mut Runtime, y: Number, x: Number| {
+            y.as_float().atan2(x.as_float())
+        })
+        .function("exp", |_: &mut Runtime, value: Number| {
+            value.as_float().exp()
+        })
+        .function("log", |_: &mut Runtime, value: Number| {
+            logarithm("log", value, f64::ln)
+        })
+        .function("log10", |_: &mut Runtime, value: Number| {
+            logarithm("log10", value, f64::log10)
+        })
+        .function("to_int", to_int)
+        .function("to_float", |_: &mut Runtime, value: Number| {
+            value.as_float()
+        })
+}
+
+fn sqrt(_: &mut Runtime, value: Number) -> Result<f64, RuntimeError> {
+    let value = value.as_float();
+    if value < 0.0 {
+        return Err(RuntimeError::Native(format!(
+            "math.sqrt is undefined for negative number {}",
+            value
+        )));
+    }
+
+    Ok(value.sqrt())
+}
+
+fn abs(_: &mut Runtime, value: Number) -> Result<Number, RuntimeError> {
+    match value {
+        Number::Int(value) => match value.checked_abs() {
+            Some(value) => Ok(Number::Int(value)),
+            None => Err(RuntimeError::Native(format!(
+                "math.abs of {} overflows an int",
+                value
+            ))),
+        },
+        Number::Float(value) => Ok(Number::Float(value.abs())),
+    }
+}
+
+fn to_int(_: &mut Runtime, value: Number) -> Result<i64, RuntimeError> {
+    match value {
+        Number::Int(value) => Ok(value),
+        Number::Float(value) => float_to_int(value.trunc()),
+    }
+}
+
+fn float_to_int(value: f64) -> Result<i64, RuntimeError> {
+    if !value.is_finite() || value < i64::MIN as f64 || value >= i64::MAX as f64 {
+        return Err(RuntimeError::Native(format!(
+            "{} cannot be converted to an int",
+            value
+        )));
+    }
+
+    Ok(value as i64)
+}
+
+fn round_with(value: Number, round: fn(f64) -> f64) -> Result<i64, RuntimeError> {
+    match value {
+        Number::Int(value) => Ok(value),
+        Number::Float(value) => float_to_int(round(value)),
+    }
+}
+
+/// Returns the operand `prefer`ed by the comparison, as a float unless both are ints
+fn pick(left: Number, right: Number, prefer: Ordering) -> Number {
+    let ordering = match (left, right) {
+        (Number::Int(left), Number::Int(right)) => left.cmp(&right),
+        _ => left
+            .as_float()
+            .partial_cmp(&right.as_float())
+            .unwrap_or(Ordering::Equal),
+    };
+
+    let picked = if ordering == prefer.reverse() {
+        right
+    } else {
+        left
+    };
+
+    match (left, right) {
+        (Number::Int(_), Number::Int(_)) => picked,
+        _ => Number::Float(picked.as_float()),
+    }
+}
+
+fn logarithm(name: &str, value: Number, log: fn(f64) -> f64) -> Result<f64, RuntimeError> {
+    let value = value.as_float();
+    if value <= 0.0 {
+        return Err(RuntimeError::Native(format!(
+            "math.{} is undefined for {}",
+            name, value
+        )));
+    }
+
+    Ok(log(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::value::Value;
+    use crate::stdlib::call;
+
+    fn math(name: &str, args: Vec<Value>) -> Result<Option<Value>, RuntimeError> {
+        let mut runtime = Runtime::new();
+        runtime.register_module(module());
+        call(&mut runtime, &format!("math.{}", name), args)
+    }
+
+    #[test]
+    fn min_and_max_keep_ints_unless_a_float_is_involved() {
+        let both_ints = vec![Value::Int(2), Value::Int(7)];
+        assert_eq!(math("min", both_ints.clone()), Ok(Some(Value::Int(2))));
+        assert_eq!(math("max", both_ints), Ok(Some(Value::Int(7))));
+
+        let mixed = vec![Value::Int(2), Value::Float(7.5)];
+        assert_eq!(math("min", mixed.clone()), Ok(Some(Value::Float(2.0))));
+        assert_eq!(math("max", mixed), Ok(Some(Value::Float(7.5))));
+    }
+
+    #[test]
+    fn abs_keeps_the_type() {
+        assert_eq!(math("abs", vec![Value::Int(-4)]), Ok(Some(Value::Int(4))));
+        assert_eq!(
+            math("abs", vec![Value::Float(-4.5)]),
+            Ok(Some(Value::Float(4.5)))
+        );
+    }
+
+    #[test]
+    fn rounding_returns_ints() {
+        assert_eq!(math("round", vec![Value::Int(3)]), Ok(Some(Value::Int(3))));
+        assert_eq!(
+            math("round", vec![Value::Float(2.5)]),
+            Ok(Some(Value::Int(3)))
+        );
+        assert_eq!(
+            math("round", vec![Value::Float(-2.5)]),
+            Ok(Some(Value::Int(-3)))
+        );
+        assert_eq!(
+            math("floor", vec![Value::Float(-0.5)]),
+            Ok(Some(Value::Int(-1)))
+        );
+        assert_eq!(
+            math("ceil", vec![Value::Float(0.1)]),
+            Ok(Some(Value::Int(1)))
+        );
+    }
+
+    #[test]
+    fn results_that_do_not_fit_an_int_are_errors() {
+        assert!(matches!(
+            math("abs", vec![Value::Int(i64::MIN)]),
+            Err(RuntimeError::Native(_))
+        ));
+        assert!(matches!(
+            math("round", vec![Value::Float(1e19)]),
+            Err(RuntimeError::Native(_))
+        ));
+        assert!(matches!(
+            math("to_int", vec![Value::Float(f64::NAN)]),
+            Err(RuntimeError::Native(_))
+        ));
+        assert_eq!(
+            math("to_int", vec![Value::Float(-7.9)]),
+            Ok(Some(Value::Int(-7)))
+        );
+    }
+
+    #[test]
+    fn domain_errors() {
+        assert!(matches!(
+            math("sqrt", vec![Value::Int(-1)]),
+            Err(RuntimeError::Native(_))
+        ));
+        assert_eq!(
+            math("sqrt", vec![Value::Int(9)]),
+            Ok(Some(Value::Float(3.0)))
+        );
+        assert!(matches!(
+            math("log", vec![Value::Float(0.0)]),
+            Err(RuntimeError::Native(_))
+        ));
+        assert!(matches!(
+            math("log10", vec![Value::Int(-10)]),
+            Err(RuntimeError::Native(_))
+        ));
+    }
+
+    #[test]
+    fn other_types_are_rejected() {
+        assert!(matches!(
+            math("abs", vec![Value::Bool(true)]),
+            Err(RuntimeError::TypeMismatch { .. })
+        ));
+    }
+}
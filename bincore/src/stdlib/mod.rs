@@ -0,0 +1,230 @@
+use crate::data::function::NativeFunction;
+use crate::executable::native::{qualified_name, NativeModule};
+use std::collections::HashMap;
+
+pub mod console;
+pub mod file;
+pub mod input;
+pub mod json;
+pub mod lista;
+pub mod math;
+pub mod process;
+pub mod random;
+pub mod teksto;
+pub mod time;
+
+/// Name and calling convention of an extern function, shared by the compiler and the runner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExternDescriptor {
+    pub name: &'static str,
+    /// number of fixed arguments, variadic externs accept any amount on top of these
+    pub arity: usize,
+    pub variadic: bool,
+}
+
+impl ExternDescriptor {
+    pub const fn fixed(name: &'static str, arity: usize) -> ExternDescriptor {
+        ExternDescriptor {
+            name,
+            arity,
+            variadic: false,
+        }
+    }
+
+    pub const fn variadic(name: &'static str, arity: usize) -> ExternDescriptor {
+        ExternDescriptor {
+            name,
+            arity,
+            variadic: true,
+        }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count == self.arity || (self.variadic && count > self.arity)
+    }
+
+    pub fn matches(&self, function: &NativeFunction) -> bool {
+        self.arity == function.arity && self.variadic == function.variadic
+    }
+}
+
+pub const BUILTINS: &[ExternDescriptor] = &[
+    ExternDescriptor::variadic("ipakita", 0),
+    ExternDescriptor::variadic("isulat", 0),
+];
+
+/// The externs a module provides, `name` is empty for the unqualified builtins
+#[derive(Debug, Clone, Copy)]
+pub struct ModuleManifest {
+    pub name: &'static str,
+    pub externs: &'static [ExternDescriptor],
+    /// externs that are called with and without the module name in front, not repeated in
+    /// `externs`
+    pub builtins: &'static [ExternDescriptor],
+}
+
+pub const STANDARD_MODULES: &[ModuleManifest] = &[
+    ModuleManifest {
+        name: "",
+        externs: BUILTINS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: lista::NAME,
+        externs: lista::EXTERNS,
+        builtins: lista::BUILTINS,
+    },
+    ModuleManifest {
+        name: math::NAME,
+        externs: math::EXTERNS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: teksto::NAME,
+        externs: teksto::EXTERNS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: input::NAME,
+        externs: input::EXTERNS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: file::NAME,
+        externs: file::EXTERNS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: time::NAME,
+        externs: time::EXTERNS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: random::NAME,
+        externs: random::EXTERNS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: json::NAME,
+        externs: json::EXTERNS,
+        builtins: &[],
+    },
+    ModuleManifest {
+        name: process::NAME,
+        externs: process::EXTERNS,
+        builtins: &[],
+    },
+];
+
+/// The unqualified output builtins, always exposed by the runner
+///
+/// The unqualified list functions come with the `lista` module, so a program that may not use
+/// `lista` cannot call them either.
+pub fn builtins() -> NativeModule {
+    console::register(NativeModule::new(""))
+}
+
+/// Looks up a standard module by name
+pub fn module(name: &str) -> Option<NativeModule> {
+    match name {
+        "" => Some(builtins()),
+        lista::NAME => Some(lista::module()),
+        math::NAME => Some(math::module()),
+        teksto::NAME => Some(teksto::module()),
+        input::NAME => Some(input::module()),
+        file::NAME => Some(file::module()),
+        time::NAME => Some(time::module()),
+        random::NAME => Some(random::module()),
+        json::NAME => Some(json::module()),
+        process::NAME => Some(process::module()),
+        _ => None,
+    }
+}
+
+/// Every extern a program may call, looked up by its qualified name
+#[derive(Debug, Clone, Default)]
+pub struct NativeRegistry {
+    pub externs: HashMap<String, ExternDescriptor>,
+}
+
+impl NativeRegistry {
+    pub fn new() -> NativeRegistry {
+        NativeRegistry::default()
+    }
+
+    /// The externs of every standard module
+    pub fn standard() -> NativeRegistry {
+        let mut registry = NativeRegistry::new();
+        for module in STANDARD_MODULES {
+            registry.extend(module);
+        }
+        registry
+    }
+
+    pub fn extend(&mut self, module: &ModuleManifest) {
+        for descriptor in module.externs {
+            self.externs
+                .insert(qualified_name(module.name, descriptor.name), *descriptor);
+        }
+        for descriptor in module.builtins {
+            self.externs
+                .insert(qualified_name(module.name, descriptor.name), *descriptor);
+            self.externs
+                .insert(descriptor.name.to_string(), *descriptor);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ExternDescriptor> {
+        self.externs.get(name)
+    }
+}
+
+/// Calls the native `name` of `runtime` with `args` in order, as `extern_call` would
+#[cfg(test)]
+pub(crate) fn call(
+    runtime: &mut crate::executable::runtime::Runtime,
+    name: &str,
+    args: Vec<crate::data::value::Value>,
+) -> Result<Option<crate::data::value::Value>, crate::executable::error::RuntimeError> {
+    let native = runtime.functions[name].function.clone();
+    native(runtime, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executable::runtime::Runtime;
+
+    #[test]
+    fn manifests_match_the_modules() {
+        for manifest in STANDARD_MODULES {
+            let module = module(manifest.name).unwrap();
+            let mut runtime = Runtime::new();
+            runtime.register_module(module);
+
+            let mut registry = NativeRegistry::new();
+            registry.extend(manifest);
+            let mut names = runtime.functions.keys().cloned().collect::<Vec<String>>();
+            let mut expected = registry.externs.keys().cloned().collect::<Vec<String>>();
+            names.sort();
+            expected.sort();
+            assert_eq!(names, expected, "module '{}'", manifest.name);
+
+            for (name, descriptor) in registry.externs.iter() {
+                assert!(descriptor.matches(&runtime.functions[name]), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn unqualified_list_functions_come_with_lista() {
+        let mut runtime = Runtime::new();
+        runtime.register_module(builtins());
+        assert!(!runtime.functions.contains_key("butngan"));
+
+        runtime.register_module(module(lista::NAME).unwrap());
+        assert!(runtime.functions.contains_key("butngan"));
+        assert!(runtime.functions.contains_key("lista.butngan"));
+        assert!(!runtime.functions.contains_key("insert"));
+    }
+}
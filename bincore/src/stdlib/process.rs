@@ -0,0 +1,94 @@
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+
+pub const NAME: &str = "process";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("args", 0),
+    ExternDescriptor::fixed("env", 1),
+    ExternDescriptor::fixed("exit", 1),
+];
+
+/// `args` returns `runtime.args`, `env` needs `Capabilities::environment` and `exit` stops the
+/// program with `RuntimeError::Exit`
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("args", |runtime: &mut Runtime| runtime.args.clone())
+        .function("env", |runtime: &mut Runtime, name: String| {
+            if !runtime.capabilities.environment {
+                return Err(RuntimeError::PermissionDenied(format!(
+                    "environment variable '{}'",
+                    name
+                )));
+            }
+            Ok(std::env::var(name).ok())
+        })
+        .function("exit", |_: &mut Runtime, code: i64| {
+            match i32::try_from(code) {
+                Ok(code) => Err::<(), RuntimeError>(RuntimeError::Exit(code)),
+                Err(_) => Err(RuntimeError::Native(format!(
+                    "Exit code {} is out of range",
+                    code
+                ))),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::value::Value;
+    use crate::executable::capabilities::Capabilities;
+    use crate::stdlib::call;
+
+    fn runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_module(module());
+        runtime
+    }
+
+    #[test]
+    fn args_are_a_list_of_strings() {
+        let mut runtime = runtime();
+        runtime.args = vec!["a".to_string(), "b c".to_string()];
+
+        let list = call(&mut runtime, "process.args", vec![]).unwrap().unwrap();
+        assert_eq!(
+            runtime.from_value::<Vec<String>>(list).unwrap(),
+            vec!["a", "b c"]
+        );
+    }
+
+    #[test]
+    fn env_needs_the_capability_and_gives_wala_when_unset() {
+        let mut runtime = runtime();
+        let name = runtime.new_string("BINISAYA_SURELY_UNSET".to_string());
+        assert_eq!(
+            call(&mut runtime, "process.env", vec![name]),
+            Err(RuntimeError::PermissionDenied(
+                "environment variable 'BINISAYA_SURELY_UNSET'".to_string()
+            ))
+        );
+
+        runtime.capabilities = Capabilities::new().allow_environment();
+        assert_eq!(
+            call(&mut runtime, "process.env", vec![name]),
+            Ok(Some(Value::Null))
+        );
+    }
+
+    #[test]
+    fn exit_stops_with_the_code() {
+        let mut runtime = runtime();
+        assert_eq!(
+            call(&mut runtime, "process.exit", vec![Value::Int(3)]),
+            Err(RuntimeError::Exit(3))
+        );
+        assert!(matches!(
+            call(&mut runtime, "process.exit", vec![Value::Int(i64::MAX)]),
+            Err(RuntimeError::Native(_))
+        ));
+    }
+}
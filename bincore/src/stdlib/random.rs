@@ -0,0 +1,45 @@
+use crate::data::convert::ListRef;
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+
+pub const NAME: &str = "random";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("int", 2),
+    ExternDescriptor::fixed("float", 0),
+    ExternDescriptor::fixed("shuffle", 1),
+    ExternDescriptor::fixed("pick", 1),
+];
+
+/// Every function draws from `runtime.random`, so a seeded runtime always gives the same results
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("int", |runtime: &mut Runtime, low: i64, high: i64| {
+            if low > high {
+                return Err(RuntimeError::Native(format!(
+                    "Cannot pick a number between {} and {}",
+                    low, high
+                )));
+            }
+            Ok(runtime.random.int_in_range(low, high))
+        })
+        .function("float", |runtime: &mut Runtime| runtime.random.float())
+        .function("shuffle", |runtime: &mut Runtime, list: ListRef| {
+            let list = runtime.lists.get_mut(&list.0).unwrap();
+            runtime.random.shuffle(list);
+        })
+        .function("pick", |runtime: &mut Runtime, list: ListRef| {
+            let length = runtime.lists[&list.0].len();
+            if length == 0 {
+                return Err(RuntimeError::Native(
+                    "Cannot pick a value from an empty list".to_string(),
+                ));
+            }
+
+            let index = runtime.random.index(length);
+            Ok::<Value, RuntimeError>(runtime.lists[&list.0][index])
+        })
+}
@@ -0,0 +1,305 @@
+use crate::data::convert::ListRef;
+use crate::data::value::Value;
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+
+pub const NAME: &str = "teksto";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("length", 1),
+    ExternDescriptor::fixed("slice", 3),
+    ExternDescriptor::fixed("split", 2),
+    ExternDescriptor::fixed("join", 2),
+    ExternDescriptor::fixed("trim", 1),
+    ExternDescriptor::fixed("upper", 1),
+    ExternDescriptor::fixed("lower", 1),
+    ExternDescriptor::fixed("contains", 2),
+    ExternDescriptor::fixed("find", 2),
+    ExternDescriptor::fixed("replace", 3),
+    ExternDescriptor::fixed("starts_with", 2),
+    ExternDescriptor::fixed("ends_with", 2),
+    ExternDescriptor::fixed("repeat", 2),
+    ExternDescriptor::fixed("to_string", 1),
+    ExternDescriptor::fixed("to_int", 1),
+    ExternDescriptor::fixed("to_float", 1),
+];
+
+/// All positions and lengths count characters, not bytes
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("length", |_: &mut Runtime, string: String| {
+            string.chars().count()
+        })
+        .function("slice", slice)
+        .function("split", split)
+        .function("join", join)
+        .function("trim", |_: &mut Runtime, string: String| {
+            string.trim().to_string()
+        })
+        .function("upper", |_: &mut Runtime, string: String| {
+            string.to_uppercase()
+        })
+        .function("lower", |_: &mut Runtime, string: String| {
+            string.to_lowercase()
+        })
+        .function(
+            "contains",
+            |_: &mut Runtime, string: String, part: String| string.contains(&part),
+        )
+        .function("find", find)
+        .function(
+            "replace",
+            |_: &mut Runtime, string: String, from: String, to: String| string.replace(&from, &to),
+        )
+        .function(
+            "starts_with",
+            |_: &mut Runtime, string: String, part: String| string.starts_with(&part),
+        )
+        .function(
+            "ends_with",
+            |_: &mut Runtime, string: String, part: String| string.ends_with(&part),
+        )
+        .function("repeat", repeat)
+        .function("to_string", to_string)
+        .function("to_int", to_int)
+        .function("to_float", to_float)
+}
+
+fn slice(_: &mut Runtime, string: String, start: i64, end: i64) -> Result<String, RuntimeError> {
+    let length = string.chars().count();
+
+    for index in [start, end] {
+        if index < 0 || index as usize > length {
+            return Err(RuntimeError::IndexOutOfBounds { index, length });
+        }
+    }
+
+    if start > end {
+        return Err(RuntimeError::Native(format!(
+            "teksto.slice start {} is after end {}",
+            start, end
+        )));
+    }
+
+    Ok(string
+        .chars()
+        .skip(start as usize)
+        .take((end - start) as usize)
+        .collect())
+}
+
+fn split(runtime: &mut Runtime, string: String, separator: String) -> Value {
+    let parts = if separator.is_empty() {
+        string.chars().map(|char| char.to_string()).collect()
+    } else {
+        string
+            .split(&separator)
+            .map(|part| part.to_string())
+            .collect::<Vec<String>>()
+    };
+
+    runtime.to_value(parts)
+}
+
+fn join(runtime: &mut Runtime, list: ListRef, separator: String) -> Result<String, RuntimeError> {
+    let parts = runtime.from_value::<Vec<String>>(Value::ListRef(list.0))?;
+    Ok(parts.join(&separator))
+}
+
+fn find(_: &mut Runtime, string: String, part: String) -> i64 {
+    match string.find(&part) {
+        Some(byte_index) => string[..byte_index].chars().count() as i64,
+        None => -1,
+    }
+}
+
+fn repeat(_: &mut Runtime, string: String, count: i64) -> Result<String, RuntimeError> {
+    if count < 0 {
+        return Err(RuntimeError::Native(format!(
+            "teksto.repeat count {} is negative",
+            count
+        )));
+    }
+
+    Ok(string.repeat(count as usize))
+}
+
+fn to_string(runtime: &mut Runtime, value: Value) -> Result<Value, RuntimeError> {
+    let string = match value {
+        Value::Int(value) => value.to_string(),
+        Value::Float(value) => value.to_string(),
+        Value::Bool(value) => value.to_string(),
+        Value::Char(value) => value.to_string(),
+        Value::StrRef(_) => return Ok(value),
+        found => {
+            return Err(RuntimeError::TypeMismatch {
+                expected: "int or float or bool or char",
+                found,
+            })
+        }
+    };
+
+    Ok(runtime.new_string(string))
+}
+
+fn to_int(_: &mut Runtime, string: String) -> Result<i64, RuntimeError> {
+    match string.trim().parse() {
+        Ok(value) => Ok(value),
+        Err(_) => Err(RuntimeError::Native(format!(
+            "'{}' is not a valid int",
+            string
+        ))),
+    }
+}
+
+fn to_float(_: &mut Runtime, string: String) -> Result<f64, RuntimeError> {
+    match string.trim().parse() {
+        Ok(value) => Ok(value),
+        Err(_) => Err(RuntimeError::Native(format!(
+            "'{}' is not a valid float",
+            string
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::call;
+
+    fn runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_module(module());
+        runtime
+    }
+
+    fn string(runtime: &mut Runtime, value: &str) -> Value {
+        runtime.new_string(value.to_string())
+    }
+
+    #[test]
+    fn lengths_and_positions_count_characters() {
+        let mut runtime = runtime();
+        let text = string(&mut runtime, "ñañal ug €uro");
+
+        assert_eq!(
+            call(&mut runtime, "teksto.length", vec![text]),
+            Ok(Some(Value::Int(13)))
+        );
+
+        let part = string(&mut runtime, "€");
+        assert_eq!(
+            call(&mut runtime, "teksto.find", vec![text, part]),
+            Ok(Some(Value::Int(9)))
+        );
+
+        let missing = string(&mut runtime, "x");
+        assert_eq!(
+            call(&mut runtime, "teksto.find", vec![text, missing]),
+            Ok(Some(Value::Int(-1)))
+        );
+
+        let slice = call(
+            &mut runtime,
+            "teksto.slice",
+            vec![text, Value::Int(1), Value::Int(4)],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(runtime.from_value::<String>(slice).unwrap(), "aña");
+    }
+
+    #[test]
+    fn slice_bounds_are_checked_in_characters() {
+        let mut runtime = runtime();
+        let text = string(&mut runtime, "€€");
+
+        let whole = call(
+            &mut runtime,
+            "teksto.slice",
+            vec![text, Value::Int(0), Value::Int(2)],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(runtime.from_value::<String>(whole).unwrap(), "€€");
+
+        assert_eq!(
+            call(
+                &mut runtime,
+                "teksto.slice",
+                vec![text, Value::Int(0), Value::Int(3)]
+            ),
+            Err(RuntimeError::IndexOutOfBounds {
+                index: 3,
+                length: 2
+            })
+        );
+        assert!(matches!(
+            call(
+                &mut runtime,
+                "teksto.slice",
+                vec![text, Value::Int(2), Value::Int(1)]
+            ),
+            Err(RuntimeError::Native(_))
+        ));
+    }
+
+    #[test]
+    fn numbers_are_parsed_after_trimming() {
+        let mut runtime = runtime();
+        let int = string(&mut runtime, " 42\n");
+        assert_eq!(
+            call(&mut runtime, "teksto.to_int", vec![int]),
+            Ok(Some(Value::Int(42)))
+        );
+
+        let float = string(&mut runtime, "-1.5");
+        assert_eq!(
+            call(&mut runtime, "teksto.to_float", vec![float]),
+            Ok(Some(Value::Float(-1.5)))
+        );
+    }
+
+    #[test]
+    fn invalid_numbers_are_errors() {
+        let mut runtime = runtime();
+        for text in ["", "4.2", "duha", "99999999999999999999"] {
+            let value = string(&mut runtime, text);
+            assert_eq!(
+                call(&mut runtime, "teksto.to_int", vec![value]),
+                Err(RuntimeError::Native(format!(
+                    "'{}' is not a valid int",
+                    text
+                )))
+            );
+        }
+
+        let value = string(&mut runtime, "1.2.3");
+        assert_eq!(
+            call(&mut runtime, "teksto.to_float", vec![value]),
+            Err(RuntimeError::Native(
+                "'1.2.3' is not a valid float".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn to_string_rejects_references_other_than_strings() {
+        let mut runtime = runtime();
+        let list = runtime.to_value(vec![1i64]);
+        assert_eq!(
+            call(&mut runtime, "teksto.to_string", vec![list]),
+            Err(RuntimeError::TypeMismatch {
+                expected: "int or float or bool or char",
+                found: list
+            })
+        );
+
+        let converted = call(&mut runtime, "teksto.to_string", vec![Value::Float(0.5)])
+            .unwrap()
+            .unwrap();
+        assert_eq!(runtime.from_value::<String>(converted).unwrap(), "0.5");
+    }
+}
@@ -0,0 +1,201 @@
+use crate::executable::error::RuntimeError;
+use crate::executable::native::NativeModule;
+use crate::executable::runtime::Runtime;
+use crate::stdlib::ExternDescriptor;
+use std::time::Duration;
+
+pub const NAME: &str = "time";
+
+pub const EXTERNS: &[ExternDescriptor] = &[
+    ExternDescriptor::fixed("now", 0),
+    ExternDescriptor::fixed("clock", 0),
+    ExternDescriptor::fixed("sleep", 1),
+    ExternDescriptor::fixed("format", 2),
+];
+
+/// Timestamps are milliseconds since the unix epoch, all of them read from the runtime's clock
+pub fn module() -> NativeModule {
+    NativeModule::new(NAME)
+        .function("now", |runtime: &mut Runtime| {
+            runtime.clock.now().as_millis() as i64
+        })
+        .function("clock", |runtime: &mut Runtime| {
+            runtime.clock.elapsed().as_secs_f64()
+        })
+        .function("sleep", |runtime: &mut Runtime, millis: i64| {
+            if millis < 0 {
+                return Err(RuntimeError::Native(format!(
+                    "Cannot sleep for {}ms",
+                    millis
+                )));
+            }
+            runtime.clock.sleep(Duration::from_millis(millis as u64));
+            Ok(())
+        })
+        .function("format", |_: &mut Runtime, millis: i64, pattern: String| {
+            format(millis, &pattern)
+        })
+}
+
+/// Formats a timestamp in UTC, `%Y %m %d %H %M %S` are replaced by the date's parts and `%%` by `%`
+pub fn format(millis: i64, pattern: &str) -> Result<String, RuntimeError> {
+    let seconds = millis.div_euclid(1000);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+
+    let mut formatted = String::new();
+    let mut chars = pattern.chars();
+    while let Some(char) = chars.next() {
+        if char != '%' {
+            formatted.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('Y') => formatted.push_str(&format!("{:04}", year)),
+            Some('m') => formatted.push_str(&format!("{:02}", month)),
+            Some('d') => formatted.push_str(&format!("{:02}", day)),
+            Some('H') => formatted.push_str(&format!("{:02}", time / 3600)),
+            Some('M') => formatted.push_str(&format!("{:02}", time % 3600 / 60)),
+            Some('S') => formatted.push_str(&format!("{:02}", time % 60)),
+            Some('%') => formatted.push('%'),
+            Some(other) => {
+                return Err(RuntimeError::Native(format!(
+                    "Unknown date format '%{}'",
+                    other
+                )))
+            }
+            None => {
+                return Err(RuntimeError::Native(
+                    "Date format ends with '%'".to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(formatted)
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) of the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::value::Value;
+    use crate::executable::clock::{Clock, FakeClock};
+    use crate::stdlib::call;
+
+    fn runtime(clock: &FakeClock) -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_module(module());
+        runtime.set_clock(clock.clone());
+        runtime
+    }
+
+    #[test]
+    fn now_and_clock_read_the_runtime_clock() {
+        let clock = FakeClock::new(Duration::from_millis(1_709_211_909_123));
+        let mut runtime = runtime(&clock);
+
+        assert_eq!(
+            call(&mut runtime, "time.now", vec![]),
+            Ok(Some(Value::Int(1_709_211_909_123)))
+        );
+        assert_eq!(
+            call(&mut runtime, "time.clock", vec![]),
+            Ok(Some(Value::Float(0.0)))
+        );
+
+        clock.advance(Duration::from_millis(1_500));
+        assert_eq!(
+            call(&mut runtime, "time.now", vec![]),
+            Ok(Some(Value::Int(1_709_211_910_623)))
+        );
+        assert_eq!(
+            call(&mut runtime, "time.clock", vec![]),
+            Ok(Some(Value::Float(1.5)))
+        );
+    }
+
+    #[test]
+    fn sleep_advances_the_clock() {
+        let clock = FakeClock::new(Duration::ZERO);
+        let mut runtime = runtime(&clock);
+
+        assert_eq!(
+            call(&mut runtime, "time.sleep", vec![Value::Int(250)]),
+            Ok(None)
+        );
+        assert_eq!(clock.elapsed(), Duration::from_millis(250));
+        assert!(matches!(
+            call(&mut runtime, "time.sleep", vec![Value::Int(-1)]),
+            Err(RuntimeError::Native(_))
+        ));
+        assert_eq!(clock.elapsed(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn formats_through_the_module() {
+        let clock = FakeClock::new(Duration::ZERO);
+        let mut runtime = runtime(&clock);
+        let pattern = runtime.new_string("%Y-%m-%d %H:%M:%S".to_string());
+
+        let formatted = call(
+            &mut runtime,
+            "time.format",
+            vec![Value::Int(1_709_211_909_123), pattern],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            runtime.from_value::<String>(formatted).unwrap(),
+            "2024-02-29 13:05:09"
+        );
+    }
+
+    #[test]
+    fn formats_known_dates() {
+        let pattern = "%Y-%m-%d %H:%M:%S";
+        assert_eq!(format(0, pattern).unwrap(), "1970-01-01 00:00:00");
+        assert_eq!(format(-1, pattern).unwrap(), "1969-12-31 23:59:59");
+        assert_eq!(
+            format(951_782_400_000, pattern).unwrap(),
+            "2000-02-29 00:00:00"
+        );
+        assert_eq!(format(0, "100%% %Y").unwrap(), "100% 1970");
+    }
+
+    #[test]
+    fn unknown_and_unfinished_patterns_are_errors() {
+        assert!(matches!(format(0, "%Q"), Err(RuntimeError::Native(_))));
+        assert!(matches!(format(0, "%Y%"), Err(RuntimeError::Native(_))));
+    }
+
+    #[test]
+    fn civil_dates_around_the_epoch_and_leap_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+        assert_eq!(civil_from_days(-135_081), (1600, 2, 29));
+    }
+}
@@ -0,0 +1,39 @@
+use arg_reader::ArgReader;
+use bincore::assembly::disassembler::disassemble;
+use bincore::data::container;
+
+fn main() {
+    let args = ArgReader::new()
+        .register("file_path")
+        .bind_with_required(vec!["i", "input"])
+        .bind_positional("file_path")
+        .read_args(std::env::args().skip(1).collect())
+        .unwrap();
+
+    let file_path = args.get_as_string("file_path").unwrap_or_else(|| {
+        eprintln!("No file specified, use -i or --input");
+        std::process::exit(1);
+    });
+
+    let input_file = match std::fs::read(&file_path) {
+        Ok(bytes) => bytes,
+        Err(error) => {
+            eprintln!("Error reading file: {}", error);
+            std::process::exit(1);
+        }
+    };
+
+    let (header, program) = match container::load(&input_file) {
+        Ok(decoded) => decoded,
+        Err(error) => {
+            eprintln!("Cannot load '{}': {}", file_path, error);
+            std::process::exit(1);
+        }
+    };
+
+    println!(
+        "; {} (format version {}, compiled by {})",
+        file_path, header.format_version, header.compiler_version
+    );
+    print!("{}", disassemble(&program));
+}
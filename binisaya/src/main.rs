@@ -0,0 +1,390 @@
+use arg_reader::{ArgMap, ArgReader};
+use bincompiler::format::format_source;
+use bincore::assembly::disassembler::disassemble;
+use bincore::data::value::Value;
+use binrunner::RunOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+mod repl;
+
+const USAGE: &str = "Usage: binisaya <command> [options]
+
+Commands:
+    run <file> [-- args]        Run a .bin source file or a compiled program
+    build <file> [-o output]    Compile a source file, --emit blc,json picks the formats, --strip drops debug info
+    check <files>               Compile and verify without running
+    disasm <file>               Print the disassembly of a source file or a compiled program
+    fmt [--check] <files>       Format source files in place, --check only lists the ones that would change
+    test [files or directories] Run every test_ function, in ./tests by default
+    repl                        Start an interactive session
+
+Run options, accepted by every command:
+    -m, --modules a,b           Native modules the program may use, all of them by default
+    --allow-fs a,b              Directories files may be accessed under, none by default
+    --allow-env                 Let the program read environment variables
+    -s, --seed <n>              Seed for the random module
+    -p, --profile <path>        Write a profile to <path>.txt and <path>.folded
+    --no-cache                  Always compile source files";
+
+fn main() {
+    let with_files = |reader: ArgReader| {
+        reader
+            .register("file")
+            .bind_with_required(vec!["i", "input"])
+            .bind_positional("file")
+            .collect_rest()
+    };
+
+    let reader = binrunner::run_flags(ArgReader::new())
+        .register("help")
+        .bind(vec!["h", "help"])
+        .subcommand("run", with_files(ArgReader::new()))
+        .subcommand(
+            "build",
+            with_files(ArgReader::new())
+                .register("output")
+                .bind_with_required(vec!["o", "output"])
+                .register("emit")
+                .bind_with_required(vec!["e", "emit"])
+                .register("strip")
+                .bind(vec!["strip"]),
+        )
+        .subcommand("check", with_files(ArgReader::new()))
+        .subcommand("disasm", with_files(ArgReader::new()))
+        .subcommand(
+            "fmt",
+            with_files(ArgReader::new())
+                .register("check")
+                .bind(vec!["check"]),
+        )
+        .subcommand("test", with_files(ArgReader::new()))
+        .subcommand("repl", ArgReader::new());
+
+    let args = match reader.read_args(std::env::args().skip(1).collect()) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let command = match args.subcommand.as_deref() {
+        Some(command) if !args.flag_is_set("help") => command,
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+
+    let code = match command {
+        "run" => run(&args),
+        "build" => build(&args),
+        "check" => check(&args),
+        "disasm" => disasm(&args),
+        "fmt" => fmt(&args),
+        "test" => test(&args),
+        "repl" => repl(&args),
+        _ => unreachable!("every subcommand is handled"),
+    };
+
+    std::process::exit(code);
+}
+
+/// The file positional followed by any other positional arguments
+fn files(args: &ArgMap) -> Vec<String> {
+    args.get_as_string("file")
+        .into_iter()
+        .chain(args.rest.iter().cloned())
+        .collect()
+}
+
+fn single_file(args: &ArgMap) -> Result<String, i32> {
+    match files(args).as_slice() {
+        [file] => Ok(file.clone()),
+        [] => {
+            eprintln!("No file specified");
+            Err(2)
+        }
+        _ => {
+            eprintln!("Expected a single file");
+            Err(2)
+        }
+    }
+}
+
+fn run_options(args: &ArgMap) -> Result<RunOptions, i32> {
+    RunOptions::from_args(args).map_err(|message| {
+        eprintln!("{}", message);
+        2
+    })
+}
+
+fn run(args: &ArgMap) -> i32 {
+    let file = match single_file(args) {
+        Ok(file) => file,
+        Err(code) => return code,
+    };
+    let options = match run_options(args) {
+        Ok(options) => options,
+        Err(code) => return code,
+    };
+
+    binrunner::run_file(&file, &options)
+}
+
+fn build(args: &ArgMap) -> i32 {
+    let file = match single_file(args) {
+        Ok(file) => file,
+        Err(code) => return code,
+    };
+
+    // compiled programs can be built too, to convert them to another format
+    let mut program = match binrunner::load_program(&file, !args.flag_is_set("no_cache")) {
+        Ok(program) => program,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+    if args.flag_is_set("strip") {
+        program.debug = None;
+    }
+
+    let formats = args
+        .get_as_string("emit")
+        .unwrap_or_else(|| "blc".to_string())
+        .split(',')
+        .map(|format| format.trim().to_string())
+        .collect::<Vec<String>>();
+    let output = args.get_as_string("output").unwrap_or_else(|| {
+        Path::new(&file)
+            .with_extension(&formats[0])
+            .to_string_lossy()
+            .to_string()
+    });
+
+    match bincompiler::write_program(&program, &output, &formats) {
+        Ok(paths) => {
+            for path in paths {
+                println!("Wrote {}", path);
+            }
+            0
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            1
+        }
+    }
+}
+
+fn check(args: &ArgMap) -> i32 {
+    let files = files(args);
+    if files.is_empty() {
+        eprintln!("No file specified");
+        return 2;
+    }
+
+    let use_cache = !args.flag_is_set("no_cache");
+    let mut failed = 0;
+    for file in files.iter() {
+        let result = binrunner::load_program(file, use_cache)
+            .and_then(|program| binrunner::verify(&program));
+
+        match result {
+            Ok(()) => println!("{}: ok", file),
+            Err(message) => {
+                eprintln!("{}:\n{}", file, message);
+                failed += 1;
+            }
+        }
+    }
+
+    (failed > 0) as i32
+}
+
+fn disasm(args: &ArgMap) -> i32 {
+    let file = match single_file(args) {
+        Ok(file) => file,
+        Err(code) => return code,
+    };
+
+    match binrunner::load_program(&file, !args.flag_is_set("no_cache")) {
+        Ok(program) => {
+            print!("{}", disassemble(&program));
+            0
+        }
+        Err(message) => {
+            eprintln!("{}", message);
+            1
+        }
+    }
+}
+
+fn fmt(args: &ArgMap) -> i32 {
+    let files = files(args);
+    if files.is_empty() {
+        eprintln!("No file specified");
+        return 2;
+    }
+
+    let check_only = args.flag_is_set("check");
+    let mut failed = 0;
+    for file in files.iter() {
+        let source = match std::fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {}", file, error);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let formatted = match format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                eprintln!("{}: {}", file, error);
+                failed += 1;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+
+        // formatting must not change what the file compiles to
+        let compiled = |source: &str| {
+            bincompiler::compile(source, file).ok().map(|mut program| {
+                program.debug = None;
+                program
+            })
+        };
+        if compiled(&source) != compiled(&formatted) {
+            eprintln!("{}: formatting would change the program, left as is", file);
+            failed += 1;
+            continue;
+        }
+
+        if check_only {
+            println!("{} is not formatted", file);
+            failed += 1;
+        } else if let Err(error) = std::fs::write(file, formatted) {
+            eprintln!("{}: {}", file, error);
+            failed += 1;
+        } else {
+            println!("Formatted {}", file);
+        }
+    }
+
+    (failed > 0) as i32
+}
+
+/// `.bin` files in `paths`, directories are searched recursively
+fn test_files(paths: &[String]) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for path in paths {
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            let mut entries = std::fs::read_dir(&path)?
+                .map(|entry| entry.map(|entry| entry.path().to_string_lossy().to_string()))
+                .collect::<std::io::Result<Vec<String>>>()?;
+            entries.sort();
+            files.extend(
+                test_files(&entries)?
+                    .into_iter()
+                    .filter(|file| file.extension().is_some_and(|ext| ext == "bin")),
+            );
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Runs the top level code of every test file and then each of its `test_` functions
+///
+/// A test fails when it raises a runtime error or returns `false`.
+fn test(args: &ArgMap) -> i32 {
+    let mut paths = files(args);
+    if paths.is_empty() {
+        paths.push("tests".to_string());
+    }
+
+    let files = match test_files(&paths) {
+        Ok(files) => files,
+        Err(error) => {
+            eprintln!("Cannot read tests: {}", error);
+            return 1;
+        }
+    };
+
+    let options = match run_options(args) {
+        Ok(options) => options,
+        Err(code) => return code,
+    };
+    let (mut passed, mut failed) = (0, 0);
+
+    for file in files {
+        let file = file.to_string_lossy().to_string();
+        let runtime = binrunner::load_program(&file, options.use_cache)
+            .and_then(|program| binrunner::prepare(program, &options));
+        let mut runtime = match runtime {
+            Ok(runtime) => runtime,
+            Err(message) => {
+                println!(
+                    "{} ... FAILED\n    {}",
+                    file,
+                    message.replace('\n', "\n    ")
+                );
+                failed += 1;
+                continue;
+            }
+        };
+
+        let result = runtime.run();
+        runtime.stdout.flush().unwrap();
+        if let Err(error) = result {
+            println!("{} ... FAILED\n    {}", file, error);
+            failed += 1;
+            continue;
+        }
+
+        let mut tests = runtime
+            .exports
+            .iter()
+            .filter(|(name, export)| name.starts_with("test_") && export.args.is_empty())
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<String>>();
+        tests.sort();
+
+        for name in tests {
+            match runtime.call_function(&name, &[]) {
+                Ok(Value::Bool(false)) => {
+                    println!("{}::{} ... FAILED\n    returned false", file, name);
+                    failed += 1;
+                }
+                Ok(_) => {
+                    println!("{}::{} ... ok", file, name);
+                    passed += 1;
+                }
+                Err(error) => {
+                    println!("{}::{} ... FAILED\n    {}", file, name, error);
+                    failed += 1;
+                }
+            }
+            runtime.stdout.flush().unwrap();
+        }
+    }
+
+    println!("\n{} passed, {} failed", passed, failed);
+    (failed > 0) as i32
+}
+
+fn repl(args: &ArgMap) -> i32 {
+    match run_options(args) {
+        Ok(options) => repl::start(&options),
+        Err(code) => code,
+    }
+}
@@ -0,0 +1,257 @@
+use bincompiler::ast::{Identifier, Span, Statement};
+use bincompiler::parser::BinLangParse;
+use bincompiler::translation::BinLangTranslationUnit;
+use bincore::assembly::disassembler::disassemble;
+use bincore::data::program_file::Program;
+use bincore::data::value::Value;
+use bincore::executable::error::RuntimeError;
+use bincore::executable::runtime::Runtime;
+use bincore::stdlib::console::value_into_printable;
+use bincore::stdlib::NativeRegistry;
+use binrunner::RunOptions;
+use std::io::Write;
+
+/// Recorded as the source file in the debug info of every input
+const SOURCE_FILE: &str = "<repl>";
+
+/// Variable an input that is a lone expression is stored in, not a name programs can use
+const RESULT: &str = "_";
+
+const HELP: &str = "Enter statements, function declarations or an expression to print its value.
+Input continues on the next line while braces are left open.
+
+Commands:
+    :help      Show this message
+    :vars      List the variables and their values
+    :disasm    Print the disassembly of the current program
+    :quit      Leave the session, as does end of input";
+
+enum Error {
+    Message(String),
+    Exit(i32),
+}
+
+/// A `Runtime` that keeps its variables, functions and heap between inputs
+///
+/// Every input is appended to the program the session runs, see `BinLangTranslationUnit::append`.
+pub struct Session {
+    runtime: Runtime,
+    /// holds the tables and labels of every input accepted so far
+    unit: BinLangTranslationUnit,
+    program: Program,
+}
+
+impl Session {
+    pub fn new(options: &RunOptions) -> Result<Session, String> {
+        let options = RunOptions {
+            profile: None,
+            ..options.clone()
+        };
+
+        let mut unit = BinLangTranslationUnit::new(vec![], vec![]);
+        let program = unit.program(SOURCE_FILE).unwrap();
+
+        let runtime = binrunner::prepare(program.clone(), &options)?;
+
+        Ok(Session {
+            runtime,
+            unit,
+            program,
+        })
+    }
+
+    /// Compiles and runs `input`, returning the value of a lone expression or the value a
+    /// function call left behind
+    fn eval(&mut self, input: &str) -> Result<Option<Value>, Error> {
+        let (statements, declarations, expression) = match BinLangParse::data(input) {
+            Ok((statements, declarations)) => (statements, declarations, false),
+            Err(error) => match BinLangParse::expression(input) {
+                Ok(expression) => {
+                    let statement = Statement::Assignment {
+                        identifier: Identifier::Single(RESULT.to_string()),
+                        expression,
+                    };
+                    let span = Span {
+                        line: 1,
+                        column: 1,
+                        end_line: input.lines().count().max(1),
+                    };
+                    (vec![(statement, span)], vec![], true)
+                }
+                Err(_) => {
+                    let error = (*error).with_path(SOURCE_FILE);
+                    return Err(Error::Message(format!("Syntax error\n{}", error)));
+                }
+            },
+        };
+
+        let mut unit = self.unit.clone();
+        let mut program = self.program.clone();
+        let entry = unit
+            .append(&mut program, statements, declarations)
+            .map_err(|errors| {
+                let errors = errors
+                    .iter()
+                    .map(|error| format!("Compile error: {}", error))
+                    .collect::<Vec<String>>();
+                Error::Message(errors.join("\n"))
+            })?;
+        binrunner::verify(&program).map_err(Error::Message)?;
+
+        self.runtime.reload(&program);
+        self.runtime
+            .link(&NativeRegistry::standard())
+            .map_err(|error| Error::Message(format!("Link error: {}", error)))?;
+        self.unit = unit;
+        self.program = program;
+
+        let runtime = &mut self.runtime;
+        let stack_base = runtime.stack_pointer;
+        runtime.instruction_pointer = entry;
+
+        let result = runtime.run();
+        runtime.stdout.flush().unwrap();
+
+        let value = match result {
+            Ok(()) if expression => Some(runtime.heap[self.unit.variable_refs[RESULT]]),
+            Ok(()) if runtime.stack_pointer > stack_base => runtime.stack_pop().ok(),
+            Ok(()) => None,
+            Err(RuntimeError::Exit(code)) => return Err(Error::Exit(code)),
+            Err(error) => {
+                let mut message = format!("Runtime error: {}", error);
+                if let Some(debug) = runtime.debug.as_ref() {
+                    for address in runtime.backtrace() {
+                        message.push_str(&format!("\n    at {}", debug.describe(address)));
+                    }
+                }
+                runtime.call_stack.clear();
+                runtime.stack_pointer = stack_base;
+                return Err(Error::Message(message));
+            }
+        };
+        runtime.stack_pointer = stack_base;
+
+        Ok(value)
+    }
+
+    fn print_variables(&self) {
+        let unit = &self.unit;
+        for index in 0..unit.variable_refs.len() {
+            let name = &unit.variable_ref_by_index[&index];
+            if name != RESULT {
+                match value_into_printable(self.runtime.heap[index], &self.runtime) {
+                    Ok(value) => println!("{} = {}", name, value),
+                    Err(error) => println!("{} = <{}>", name, error),
+                }
+            }
+        }
+    }
+}
+
+/// Braces opened in `source` and not closed yet, ignoring the ones in strings and comments
+fn open_braces(source: &str) -> i64 {
+    let mut depth = 0;
+    let mut chars = source.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '"' => {
+                while let Some(char) = chars.next() {
+                    match char {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for char in chars.by_ref() {
+                    if char == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for char in chars.by_ref() {
+                    if previous == '*' && char == '/' {
+                        break;
+                    }
+                    previous = char;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    depth
+}
+
+/// Reads inputs from stdin until `:quit` or the end of input, returning the exit code
+pub fn start(options: &RunOptions) -> i32 {
+    let mut session = match Session::new(options) {
+        Ok(session) => session,
+        Err(message) => {
+            eprintln!("{}", message);
+            return 1;
+        }
+    };
+
+    println!(
+        "binisaya {}, :help lists the commands",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let stdin = std::io::stdin();
+    let mut input = String::new();
+    loop {
+        print!("{}", if input.is_empty() { ">> " } else { ".. " });
+        std::io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) => {
+                println!();
+                return 0;
+            }
+            Ok(_) => {}
+            Err(error) => {
+                eprintln!("Error reading input: {}", error);
+                return 1;
+            }
+        }
+
+        let command = line.trim();
+        if input.is_empty() && (command.is_empty() || command.starts_with(':')) {
+            match command {
+                "" => {}
+                ":help" => println!("{}", HELP),
+                ":vars" => session.print_variables(),
+                ":disasm" => print!("{}", disassemble(&session.program)),
+                ":quit" | ":exit" => return 0,
+                _ => eprintln!("Unknown command {}, :help lists the commands", command),
+            }
+            continue;
+        }
+
+        input.push_str(&line);
+        if open_braces(&input) > 0 {
+            continue;
+        }
+
+        match session.eval(&std::mem::take(&mut input)) {
+            Ok(Some(value)) => match value_into_printable(value, &session.runtime) {
+                Ok(value) => println!("{}", value),
+                Err(error) => eprintln!("Runtime error: {}", error),
+            },
+            Ok(None) => {}
+            Err(Error::Message(message)) => eprintln!("{}", message),
+            Err(Error::Exit(code)) => return code,
+        }
+    }
+}
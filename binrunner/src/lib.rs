@@ -0,0 +1,281 @@
+use arg_reader::{ArgMap, ArgReader};
+use bincompiler::cache::Cache;
+use bincompiler::CompileError;
+use bincore::data::container;
+use bincore::data::program_file::Program;
+use bincore::executable::capabilities::Capabilities;
+use bincore::executable::error::RuntimeError;
+use bincore::executable::random::Random;
+use bincore::executable::runtime::Runtime;
+use bincore::executable::verifier;
+use bincore::stdlib;
+use bincore::stdlib::{NativeRegistry, STANDARD_MODULES};
+use std::io::Write;
+
+/// Registers the flags every way of running a program accepts, read back by `RunOptions::from_args`
+pub fn run_flags(reader: ArgReader) -> ArgReader {
+    reader
+        .register("profile")
+        .bind_with_required(vec!["p", "profile"])
+        .register("modules")
+        .bind_with_required(vec!["m", "modules"])
+        .register("allow_fs")
+        .bind_with_required(vec!["allow-fs"])
+        .register("allow_env")
+        .bind(vec!["allow-env"])
+        .register("no_cache")
+        .bind(vec!["no-cache"])
+        .register("seed")
+        .bind_with_required(vec!["s", "seed"])
+}
+
+/// How the `Runtime` a program runs in is set up
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// native modules available besides the builtins
+    pub modules: Vec<String>,
+    /// directories scripts may touch files under
+    pub file_roots: Vec<String>,
+    /// whether scripts may read environment variables
+    pub environment: bool,
+    pub seed: Option<u64>,
+    /// path the profile is written to, without extension
+    pub profile: Option<String>,
+    pub use_cache: bool,
+    /// arguments passed to the program itself
+    pub args: Vec<String>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            modules: STANDARD_MODULES
+                .iter()
+                .map(|module| module.name.to_string())
+                .collect(),
+            // sandboxed like an embedded `Runtime`, access is only given when asked for
+            file_roots: vec![],
+            environment: false,
+            seed: None,
+            profile: None,
+            use_cache: true,
+            args: vec![],
+        }
+    }
+}
+
+impl RunOptions {
+    /// The options `run_flags` registered, or what is wrong with one of them
+    ///
+    /// # Example
+    /// ```
+    /// use arg_reader::ArgReader;
+    /// use binrunner::RunOptions;
+    ///
+    /// let read = |args: &[&str]| {
+    ///     let args = args.iter().map(|arg| arg.to_string()).collect();
+    ///     RunOptions::from_args(&binrunner::run_flags(ArgReader::new()).read_args(args).unwrap())
+    /// };
+    ///
+    /// assert_eq!(read(&["--seed", "18446744073709551615"]).unwrap().seed, Some(u64::MAX));
+    /// assert!(read(&["--seed", "-1"]).is_err());
+    /// assert!(read(&["--seed", "abc"]).is_err());
+    ///
+    /// assert!(read(&[]).unwrap().file_roots.is_empty());
+    /// assert!(!read(&[]).unwrap().environment);
+    /// assert_eq!(read(&["--allow-fs", "42,out"]).unwrap().file_roots, vec!["42", "out"]);
+    /// assert!(read(&["--allow-env"]).unwrap().environment);
+    /// ```
+    pub fn from_args(args: &ArgMap) -> Result<RunOptions, String> {
+        let defaults = RunOptions::default();
+
+        let list = |value: String| {
+            value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect::<Vec<String>>()
+        };
+
+        // read as passed, a number too large for an int would not be one otherwise
+        let seed = match args.get_raw("seed") {
+            Some(seed) => Some(seed.parse::<u64>().map_err(|_| {
+                format!(
+                    "Invalid seed '{}', expected a whole number from 0 to {}",
+                    seed,
+                    u64::MAX
+                )
+            })?),
+            None => None,
+        };
+
+        Ok(RunOptions {
+            modules: args
+                .get_as_string("modules")
+                .map(list)
+                .unwrap_or(defaults.modules),
+            // read as passed, a directory named 42 would be an int otherwise
+            file_roots: args
+                .get_raw("allow_fs")
+                .map(list)
+                .unwrap_or(defaults.file_roots),
+            environment: args.flag_is_set("allow_env"),
+            seed,
+            profile: args.get_raw("profile"),
+            use_cache: !args.flag_is_set("no_cache"),
+            args: args.trailing.clone(),
+        })
+    }
+}
+
+/// Reads a compiled program, or compiles a `.bin` source file in memory
+pub fn load_program(file_path: &str, use_cache: bool) -> Result<Program, String> {
+    if file_path.ends_with(".bin") {
+        let source = std::fs::read_to_string(file_path)
+            .map_err(|error| format!("Error reading file: {}", error))?;
+
+        let cache = Cache::default_directory()
+            .filter(|_| use_cache)
+            .map(Cache::new);
+        let result = match cache {
+            Some(cache) => cache.compile(&source, file_path),
+            None => bincompiler::compile(&source, file_path),
+        };
+
+        return result.map_err(|error| match error {
+            CompileError::Translation(errors) => errors
+                .iter()
+                .map(|error| format!("Compile error: {}", error))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            error => format!("Compile error: {}", error),
+        });
+    }
+
+    let input_file =
+        std::fs::read(file_path).map_err(|error| format!("Error reading file: {}", error))?;
+    match container::load(&input_file) {
+        Ok((_, program)) => Ok(program),
+        Err(error) => Err(format!("Cannot load '{}': {}", file_path, error)),
+    }
+}
+
+pub fn verify(program: &Program) -> Result<(), String> {
+    verifier::verify(program).map_err(|errors| {
+        errors
+            .iter()
+            .map(|error| format!("Verify error: {}", error))
+            .collect::<Vec<String>>()
+            .join("\n")
+    })
+}
+
+/// Verifies `program` and sets up a linked `Runtime` for it as described by `options`
+pub fn prepare(program: Program, options: &RunOptions) -> Result<Runtime, String> {
+    verify(&program)?;
+
+    let mut runtime = program.into_runtime();
+
+    // the unqualified builtins are always available, `modules` picks the named ones
+    runtime.register_module(stdlib::builtins());
+    for name in options.modules.iter() {
+        match stdlib::module(name) {
+            Some(module) => runtime.register_module(module),
+            None => return Err(format!("Unknown module: {}", name)),
+        }
+    }
+
+    runtime.capabilities =
+        options
+            .file_roots
+            .iter()
+            .try_fold(Capabilities::new(), |capabilities, root| {
+                capabilities
+                    .allow_directory(root)
+                    .map_err(|error| format!("Cannot allow file access to '{}': {}", root, error))
+            })?;
+    if options.environment {
+        runtime.capabilities = runtime.capabilities.allow_environment();
+    }
+    runtime.args = options.args.clone();
+
+    if let Some(seed) = options.seed {
+        runtime.random = Random::new(seed);
+    }
+
+    runtime
+        .link(&NativeRegistry::standard())
+        .map_err(|error| format!("Link error: {}", error))?;
+
+    if options.profile.is_some() {
+        runtime.enable_profiling();
+    }
+
+    Ok(runtime)
+}
+
+/// Runs the top level code of a prepared `Runtime` and returns the exit code of the process
+///
+/// Runtime errors are written to the runtime's stderr, with a backtrace when the program has
+/// debug info.
+pub fn execute(runtime: &mut Runtime, options: &RunOptions) -> i32 {
+    let result = runtime.run();
+    runtime.stdout.flush().unwrap();
+
+    let mut profiled = true;
+    if let Some(output) = &options.profile {
+        if let Err(error) = write_profile(runtime, output) {
+            writeln!(runtime.stderr, "Cannot write profile: {}", error).unwrap();
+            profiled = false;
+        }
+    }
+
+    match result {
+        Ok(()) if !profiled => 1,
+        Ok(()) => 0,
+        Err(RuntimeError::Exit(code)) => code,
+        Err(error) => {
+            writeln!(runtime.stderr, "Runtime error: {}", error).unwrap();
+            if let Some(debug) = runtime.debug.as_ref() {
+                for address in runtime.backtrace() {
+                    writeln!(runtime.stderr, "    at {}", debug.describe(address)).unwrap();
+                }
+            }
+            1
+        }
+    }
+}
+
+/// Loads, prepares and runs `file_path`, returning the exit code of the process
+pub fn run_file(file_path: &str, options: &RunOptions) -> i32 {
+    let runtime =
+        load_program(file_path, options.use_cache).and_then(|program| prepare(program, options));
+
+    match runtime {
+        Ok(mut runtime) => execute(&mut runtime, options),
+        Err(message) => {
+            eprintln!("{}", message);
+            1
+        }
+    }
+}
+
+/// Writes `<output>.txt` and `<output>.folded` for a runtime that ran with profiling enabled
+fn write_profile(runtime: &Runtime, output: &str) -> std::io::Result<()> {
+    let profiler = match runtime.profiler.as_ref() {
+        Some(profiler) => profiler,
+        None => return Ok(()),
+    };
+
+    let report_path = format!("{}.txt", output);
+    let folded_path = format!("{}.folded", output);
+
+    let mut report = std::fs::File::create(&report_path)?;
+    profiler.write_report(&runtime.instructions, &mut report)?;
+
+    let mut folded = std::fs::File::create(&folded_path)?;
+    profiler.write_folded(&mut folded)?;
+
+    eprintln!("Profile written to {} and {}", report_path, folded_path);
+    Ok(())
+}